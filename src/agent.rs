@@ -0,0 +1,307 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::betting::{Action, LegalActions};
+use crate::deck::Card;
+use crate::env::DiscreteAction;
+use crate::equity::{EquityMode, MonteCarloConfig};
+use crate::evaluator::{evaluate, HandCategory};
+use crate::holdem::{HoldemGame, Stage};
+use crate::observation::Observation;
+use crate::range::{Combo, Range};
+
+// a decision-maker for one seat. it only ever sees the seat's own observation.
+pub trait Agent: Send {
+    fn name(&self) -> &str;
+
+    // called on the seat's turn only, so the observation always holds legal actions.
+    fn act(&mut self, obs: &Observation) -> Action;
+}
+
+fn legal(obs: &Observation) -> LegalActions {
+    obs.legal_actions.expect("agents act on their own turn only")
+}
+
+fn check_or(legal: &LegalActions, otherwise: Action) -> Action {
+    if legal.can_check { Action::Check } else { otherwise }
+}
+
+// a raise of the given size, or a call when raising is closed.
+fn raise_or_call(obs: &Observation, size: DiscreteAction) -> Action {
+    let legal = legal(obs);
+    size.to_action(&legal, obs.pot).unwrap_or(check_or(&legal, Action::Call))
+}
+
+// the category of the board on its own. short of five cards, only sets of ranks count.
+fn board_category(board: &[Card]) -> HandCategory {
+    if board.len() >= 5 {
+        return evaluate(board.iter().copied().collect()).category();
+    }
+    let mut counts = [0; 13];
+    board.iter().for_each(|card| counts[card.rank.index()] += 1);
+    let pairs = counts.iter().filter(|&&count| count == 2).count();
+    match counts.iter().max().copied().unwrap_or(0) {
+        4 => HandCategory::FourOfAKind,
+        3 => HandCategory::ThreeOfAKind,
+        2 if pairs == 2 => HandCategory::TwoPairs,
+        2 => HandCategory::OnePair,
+        _ => HandCategory::Top,
+    }
+}
+
+// picks among folding, checking or calling and raising with equal odds, a raise going to
+// any legal size.
+#[derive(Debug, Clone)]
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn act(&mut self, obs: &Observation) -> Action {
+        let legal = legal(obs);
+        let mut choices = vec![check_or(&legal, Action::Call)];
+        if !legal.can_check {
+            choices.push(Action::Fold);
+        }
+        if legal.can_raise {
+            let to = self.rng.gen_range(legal.min_raise_to..=legal.max_raise_to);
+            choices.push(if legal.current_bet == 0 { Action::Bet(to) } else { Action::Raise(to) });
+        }
+        choices[self.rng.gen_range(0..choices.len())]
+    }
+}
+
+// never folds and never raises.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallingStation;
+
+impl Agent for CallingStation {
+    fn name(&self) -> &str {
+        "calling station"
+    }
+
+    fn act(&mut self, obs: &Observation) -> Action {
+        check_or(&legal(obs), Action::Call)
+    }
+}
+
+// a tight-aggressive rule bot. preflop it raises its opening range, re-raises premium hands
+// and calls a raise with its calling range. after the flop it bets two pair or better at
+// the pot when its hole cards improve on the board, bets a pair at half the pot and calls up
+// to half the pot with it, and gives up with nothing.
+#[derive(Debug, Clone)]
+pub struct TightAggressive {
+    pub open: Range,
+    pub call: Range,
+    pub reraise: Range,
+}
+
+impl Default for TightAggressive {
+    fn default() -> Self {
+        Self {
+            open: "66+, A9s+, KTs+, QTs+, JTs, T9s, ATo+, KJo+, QJo".parse().unwrap(),
+            call: "88+, AJs+, KQs, AQo+".parse().unwrap(),
+            reraise: "QQ+, AKs, AKo".parse().unwrap(),
+        }
+    }
+}
+
+impl Agent for TightAggressive {
+    fn name(&self) -> &str {
+        "tight aggressive"
+    }
+
+    fn act(&mut self, obs: &Observation) -> Action {
+        let legal = legal(obs);
+        let cards = obs.hole_cards.to_vec();
+        let combo = Combo::new(cards[0], cards[1]).expect("two different hole cards");
+        if obs.stage == Stage::PreFlop {
+            let raised = legal.current_bet > obs.blinds.big_blind;
+            if self.reraise.contains(combo) || (!raised && self.open.contains(combo)) {
+                return raise_or_call(obs, DiscreteAction::Pot);
+            }
+            if raised && self.call.contains(combo) {
+                return Action::Call;
+            }
+            return check_or(&legal, Action::Fold);
+        }
+        // a made hand only counts when the hole cards lift it above what the board shows alone.
+        let category = evaluate(obs.hole_cards.union(obs.board.iter().copied().collect())).category();
+        let strong = category >= HandCategory::TwoPairs && category > board_category(&obs.board);
+        // a pocket pair, or a hole card paired on the board.
+        let holds_a_pair =
+            cards[0].rank == cards[1].rank || obs.board.iter().any(|card| cards.iter().any(|hole| hole.rank == card.rank));
+        let half_pot = legal.call_amount * 2 <= obs.pot;
+        if strong {
+            raise_or_call(obs, DiscreteAction::Pot)
+        } else if holds_a_pair && legal.can_check {
+            raise_or_call(obs, DiscreteAction::HalfPot)
+        } else if holds_a_pair && half_pot {
+            Action::Call
+        } else {
+            check_or(&legal, Action::Fold)
+        }
+    }
+}
+
+// estimates its equity against random hands of the players left in, with HoldemGame. it
+// raises at the pot from raise_threshold, and calls when the equity beats both
+// call_threshold and the pot odds.
+#[derive(Debug, Clone)]
+pub struct EquityAgent {
+    pub raise_threshold: f64,
+    pub call_threshold: f64,
+    pub iterations: usize,
+    rng: StdRng,
+}
+
+impl EquityAgent {
+    pub fn new(raise_threshold: f64, call_threshold: f64, seed: u64) -> Self {
+        Self { raise_threshold, call_threshold, iterations: 2000, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn equity(&mut self, obs: &Observation) -> f64 {
+        let players = obs.folded.iter().filter(|&&folded| !folded).count();
+        let mut game = HoldemGame::with_seed(players, self.rng.gen());
+        game.set_hole_cards(0, obs.hole_cards.to_vec()).expect("the seat is free");
+        if !obs.board.is_empty() {
+            game.set_board_cards(obs.board.clone()).expect("the board is a real one");
+        }
+        let mode = EquityMode::MonteCarlo(MonteCarloConfig::new(self.iterations));
+        game.calc_equity_with_threads(mode, 1).expect("the game holds no range").equity
+    }
+}
+
+impl Agent for EquityAgent {
+    fn name(&self) -> &str {
+        "equity threshold"
+    }
+
+    fn act(&mut self, obs: &Observation) -> Action {
+        let legal = legal(obs);
+        let equity = self.equity(obs);
+        let pot_odds = legal.call_amount as f64 / (obs.pot + legal.call_amount) as f64;
+        if equity >= self.raise_threshold && legal.can_raise {
+            raise_or_call(obs, DiscreteAction::Pot)
+        } else if legal.can_check {
+            Action::Check
+        } else if equity >= self.call_threshold.max(pot_odds) {
+            Action::Call
+        } else {
+            Action::Fold
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_for_agent {
+    use crate::agent::*;
+    use crate::betting::*;
+    use crate::deck::*;
+    use crate::env::*;
+    use crate::holdem::HoldemGame;
+    use crate::observation::Observation;
+
+    // plays hands between the agents and checks every action they take is legal.
+    fn self_play(agents: &mut [Box<dyn Agent>], hands: u64) -> Vec<ActionRecord> {
+        let mut env = HoldemEnv::new(EnvConfig::new(agents.len(), 200, Blinds::new(1, 2))).unwrap();
+        let mut records = Vec::new();
+        for seed in 0..hands {
+            let mut obs = env.reset(seed);
+            while let Some(seat) = obs.to_act {
+                let action = agents[seat].act(&env.observe(seat));
+                assert!(obs.legal_actions.unwrap().allows(action), "{} took {:?}", agents[seat].name(), action);
+                let (next, _, _, info) = env.step(action).unwrap();
+                records.push(info.record);
+                obs = next;
+            }
+        }
+        records
+    }
+
+    fn observation(holes: &str, stacks: Vec<u64>, actions: &[Action]) -> Observation {
+        let mut game = HoldemGame::with_seed(stacks.len(), 3);
+        game.set_hole_cards(0, parse_cards(holes).unwrap()).unwrap();
+        let mut table = HoldemTable::new(game, stacks, 1, Blinds::new(1, 2)).unwrap();
+        for &action in actions {
+            table.act(action).unwrap();
+        }
+        Observation::new(&table, 0)
+    }
+
+    #[test]
+    fn bots_only_take_legal_actions() {
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(RandomAgent::new(1)),
+            Box::new(CallingStation),
+            Box::new(TightAggressive::default()),
+            Box::new(EquityAgent::new(0.6, 0.3, 2).with_iterations(200)),
+        ];
+        let records = self_play(&mut agents, 40);
+        let station: Vec<Action> = records.iter().filter(|record| record.seat == 1).map(|record| record.action).collect();
+        assert!(station.iter().all(|&action| action == Action::Check || action == Action::Call));
+        assert!(records.iter().any(|record| record.seat == 0 && matches!(record.action, Action::Bet(_) | Action::Raise(_))));
+    }
+
+    #[test]
+    fn tight_aggressive_preflop() {
+        let mut tag = TightAggressive::default();
+        // three-handed with the button on seat 1, seat 0 is the big blind.
+        let obs = observation("Ah Ad", vec![100; 3], &[Action::Raise(6), Action::Call]);
+        assert!(matches!(tag.act(&obs), Action::Raise(_)));
+        let obs = observation("7h 2d", vec![100; 3], &[Action::Raise(6), Action::Call]);
+        assert_eq!(tag.act(&obs), Action::Fold);
+        let obs = observation("9h 9d", vec![100; 3], &[Action::Raise(6), Action::Call]);
+        assert_eq!(tag.act(&obs), Action::Call);
+        // a weak big blind checks its option.
+        let obs = observation("7h 2d", vec![100; 3], &[Action::Call, Action::Call]);
+        assert_eq!(tag.act(&obs), Action::Check);
+    }
+
+    #[test]
+    fn tight_aggressive_postflop() {
+        let mut tag = TightAggressive::default();
+        // heads-up, both limp and seat 0 opens the betting on the board below.
+        let on_board = |holes: &str, board: &str| {
+            let mut obs = observation(holes, vec![100; 2], &[Action::Call, Action::Check]);
+            obs.board = parse_cards(board).unwrap();
+            obs
+        };
+        // the board's two pair is everybody's : ace high alone is no hand.
+        assert_eq!(tag.act(&on_board("Ah 3c", "Kd Kc 7s 7h 2d")), Action::Check);
+        assert_eq!(tag.act(&on_board("Ah 3c", "Kd Kc Ks 7h 2d")), Action::Check);
+        assert!(matches!(tag.act(&on_board("7c 3d", "Kd Kc 7s 7h 2d")), Action::Bet(_)));
+        assert!(matches!(tag.act(&on_board("Kh 7c", "Kd 7s 2d")), Action::Bet(_)));
+        // a pair bets half the pot, nothing checks.
+        assert!(matches!(tag.act(&on_board("Qh Qc", "Kd 7s 2d")), Action::Bet(_)));
+        assert_eq!(tag.act(&on_board("Qh Jc", "Kd 7s 2d")), Action::Check);
+    }
+
+    #[test]
+    fn equity_agent_follows_its_thresholds() {
+        let mut agent = EquityAgent::new(0.7, 0.2, 5).with_iterations(2000);
+        let obs = observation("Ah Ad", vec![100; 2], &[Action::Raise(6)]);
+        assert!(agent.equity(&obs) > 0.8);
+        assert!(matches!(agent.act(&obs), Action::Raise(_)));
+        // an all-in leaves 7-2 offsuit far from the pot odds it needs.
+        let obs = observation("7h 2d", vec![100; 2], &[Action::Raise(100)]);
+        assert_eq!(agent.act(&obs), Action::Fold);
+        let mut loose = EquityAgent::new(0.9, 0.0, 5).with_iterations(2000);
+        let obs = observation("7h 2d", vec![100; 2], &[Action::Call]);
+        assert_eq!(loose.act(&obs), Action::Check);
+    }
+}
@@ -0,0 +1,184 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::agent::Agent;
+use crate::betting::HoldemTable;
+use crate::env::EnvConfig;
+use crate::observation::Observation;
+
+// plays a hand to the end, seat i asking agents[i]. each agent only gets its own observation.
+pub fn play_hand(table: &mut HoldemTable, agents: &mut [Box<dyn Agent>]) -> Result<(), &'static str> {
+    if agents.len() != table.seats() {return Err("One agent per seat is needed.");}
+    while let Some(seat) = table.to_act() {
+        let action = agents[seat].act(&Observation::new(table, seat));
+        table.act(action).map_err(|_| "An agent took an illegal action.")?;
+    }
+    Ok(())
+}
+
+// the results of one agent, in thousandths of a big blind per hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentResult {
+    pub name: String,
+    pub hands: u64,
+    pub mbb_per_hand: f64,
+    // standard error of mbb_per_hand, each deal and its replays counting as one sample.
+    pub std_error: f64,
+    // results by seats after the button, 0 being the button.
+    pub positions: Vec<PositionResult>,
+}
+
+impl AgentResult {
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        (self.mbb_per_hand - z * self.std_error, self.mbb_per_hand + z * self.std_error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionResult {
+    pub hands: u64,
+    pub mbb_per_hand: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaReport {
+    pub deals: u64,
+    pub hands: u64,
+    pub agents: Vec<AgentResult>,
+}
+
+// plays agents against each other in duplicate : every deal is replayed once per seating,
+// the agents moving one seat along each time while the cards and the button stay put. the
+// luck of the cards then cancels out between the replays.
+#[derive(Debug, Clone)]
+pub struct Arena {
+    config: EnvConfig,
+    deals: u64,
+    seed: u64,
+}
+
+impl Arena {
+    pub fn new(config: EnvConfig, deals: u64, seed: u64) -> Self {
+        Self { config, deals, seed }
+    }
+
+    pub fn run(&self, agents: &mut [Box<dyn Agent>]) -> Result<ArenaReport, &'static str> {
+        let n = agents.len();
+        if n != self.config.players {return Err("One agent per seat is needed.");}
+        let big_blind = self.config.blinds.big_blind as f64;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        // per deal sums and squares of each agent's average over the seatings.
+        let mut sums = vec![0.0; n];
+        let mut squares = vec![0.0; n];
+        let mut positions = vec![vec![(0u64, 0i64); n]; n];
+        for deal in 0..self.deals {
+            let seed: u64 = rng.gen();
+            let button = deal as usize % n;
+            let mut results = vec![0i64; n];
+            for shift in 0..n {
+                let mut table = self.config.deal(button, seed)?;
+                // agent i sits on seat (i + shift) % n.
+                agents.rotate_right(shift);
+                let played = play_hand(&mut table, agents);
+                agents.rotate_left(shift);
+                played?;
+                for (seat, payoff) in table.payoffs().into_iter().enumerate() {
+                    let agent = (seat + n - shift) % n;
+                    results[agent] += payoff;
+                    let position = &mut positions[agent][(seat + n - button) % n];
+                    position.0 += 1;
+                    position.1 += payoff;
+                }
+            }
+            for agent in 0..n {
+                let mbb = results[agent] as f64 / n as f64 / big_blind * 1000.0;
+                sums[agent] += mbb;
+                squares[agent] += mbb * mbb;
+            }
+        }
+        let deals = self.deals as f64;
+        let agents = agents
+            .iter()
+            .enumerate()
+            .map(|(agent, bot)| {
+                let mean = if self.deals > 0 { sums[agent] / deals } else { 0.0 };
+                let variance = if self.deals > 1 { (squares[agent] - deals * mean * mean).max(0.0) / (deals - 1.0) } else { 0.0 };
+                AgentResult {
+                    name: bot.name().to_string(),
+                    hands: self.deals * n as u64,
+                    mbb_per_hand: mean,
+                    std_error: if self.deals > 0 { (variance / deals).sqrt() } else { 0.0 },
+                    positions: positions[agent]
+                        .iter()
+                        .map(|&(hands, chips)| PositionResult {
+                            hands,
+                            mbb_per_hand: if hands > 0 { chips as f64 / hands as f64 / big_blind * 1000.0 } else { 0.0 },
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(ArenaReport { deals: self.deals, hands: self.deals * n as u64, agents })
+    }
+}
+
+#[cfg(test)]
+mod test_for_arena {
+    use crate::agent::*;
+    use crate::arena::*;
+    use crate::betting::*;
+    use crate::holdem::HoldemGame;
+
+    fn config(players: usize) -> EnvConfig {
+        EnvConfig::new(players, 200, Blinds::new(1, 2))
+    }
+
+    #[test]
+    fn mirrored_agents_break_even_exactly() {
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(CallingStation), Box::new(CallingStation)];
+        let report = Arena::new(config(2), 50, 1).run(&mut agents).unwrap();
+        assert_eq!((report.deals, report.hands), (50, 100));
+        for result in &report.agents {
+            // the same strategy on both sides of every deal cancels the cards out.
+            assert_eq!((result.mbb_per_hand, result.std_error), (0.0, 0.0));
+            assert_eq!(result.positions.iter().map(|position| position.hands).sum::<u64>(), 100);
+        }
+    }
+
+    #[test]
+    fn duplicate_results_are_zero_sum() {
+        let mut agents: Vec<Box<dyn Agent>> =
+            vec![Box::new(CallingStation), Box::new(RandomAgent::new(2)), Box::new(TightAggressive::default())];
+        let report = Arena::new(config(3), 100, 7).run(&mut agents).unwrap();
+        assert_eq!(report.hands, 300);
+        let total: f64 = report.agents.iter().map(|result| result.mbb_per_hand).sum();
+        assert!(total.abs() < 1e-6);
+        for result in &report.agents {
+            assert_eq!(result.positions.len(), 3);
+            assert!(result.positions.iter().all(|position| position.hands == 100));
+            let (low, high) = result.confidence_interval(1.96);
+            assert!(low <= result.mbb_per_hand && result.mbb_per_hand <= high);
+        }
+        assert!(report.agents.iter().all(|result| result.std_error > 0.0));
+        assert!(Arena::new(config(2), 10, 7).run(&mut agents).is_err());
+    }
+
+    #[test]
+    fn replays_deal_the_same_cards() {
+        let config = config(3);
+        let first = config.deal(1, 99).unwrap();
+        let again = config.deal(1, 99).unwrap();
+        for seat in 0..3 {
+            assert_eq!(first.game().hole_cards(seat), again.game().hole_cards(seat));
+        }
+        // a hand played out runs the same board whatever the agents did.
+        let mut stations: Vec<Box<dyn Agent>> = vec![Box::new(CallingStation), Box::new(CallingStation), Box::new(CallingStation)];
+        let mut table = config.deal(1, 99).unwrap();
+        play_hand(&mut table, &mut stations).unwrap();
+        let mut game = HoldemGame::with_seed(3, 99);
+        game.deal_hole_cards();
+        game.preflop_to_flop();
+        game.flop_to_turn();
+        game.turn_to_river();
+        assert_eq!(table.game().board_cards(), game.board_cards());
+    }
+}
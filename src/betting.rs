@@ -0,0 +1,719 @@
+use std::fmt;
+use std::sync::Arc;
+use crate::grade::Grade;
+use crate::holdem::{HoldemGame, Stage};
+use crate::pot::{award_pots, build_pots, OddChipRule, Pot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    // bets and raises name the player's total on the street : Raise(300) raises to 300.
+    Bet(u64),
+    Raise(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blinds {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+}
+
+impl Blinds {
+    pub fn new(small_blind: u64, big_blind: u64) -> Self {
+        Self { small_blind, big_blind, ante: 0 }
+    }
+
+    pub fn with_ante(mut self, ante: u64) -> Self {
+        self.ante = ante;
+        self
+    }
+}
+
+// an action as it was taken, with the chips it put in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionRecord {
+    pub stage: Stage,
+    pub seat: usize,
+    pub action: Action,
+    pub amount: u64,
+}
+
+// what the player to act may do. a bet or raise goes to a street total between
+// min_raise_to and max_raise_to, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegalActions {
+    pub current_bet: u64,
+    pub call_amount: u64,
+    pub can_check: bool,
+    pub can_raise: bool,
+    pub min_raise_to: u64,
+    pub max_raise_to: u64,
+}
+
+impl LegalActions {
+    pub fn check(&self, action: Action) -> Result<(), &'static str> {
+        match action {
+            Action::Fold => Ok(()),
+            Action::Check if !self.can_check => Err("Cannot check facing a bet."),
+            Action::Call if self.call_amount == 0 => Err("Nothing to call."),
+            Action::Bet(_) if self.current_bet > 0 => Err("Cannot bet facing a bet, raise instead."),
+            Action::Raise(_) if self.current_bet == 0 => Err("No bet to raise."),
+            Action::Bet(to) | Action::Raise(to) => {
+                if !self.can_raise {
+                    return Err("Raising is not allowed.");
+                }
+                if to < self.min_raise_to || to > self.max_raise_to {
+                    return Err("Bet size is out of range.");
+                }
+                Ok(())
+            }
+            Action::Check | Action::Call => Ok(()),
+        }
+    }
+
+    pub fn allows(&self, action: Action) -> bool {
+        self.check(action).is_ok()
+    }
+}
+
+// what a betting structure needs to know to size a bet or raise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaiseSpot {
+    pub stage: Stage,
+    pub big_blind: u64,
+    // the street total to match, and the size of the last full raise.
+    pub current_bet: u64,
+    pub min_raise: u64,
+    // what the seat to act has put in on the street.
+    pub street_bet: u64,
+    // every chip put in so far, bets of the street included.
+    pub pot: u64,
+}
+
+impl RaiseSpot {
+    // the no-limit minimum : the big blind to open, else the last raise again.
+    pub fn min_raise_to(&self) -> u64 {
+        match self.current_bet {
+            0 => self.big_blind,
+            bet => bet + self.min_raise,
+        }
+    }
+}
+
+// the sizes a bet or raise may take. the table still lets a short stack go all-in for less.
+pub trait BettingStructure: fmt::Debug + Send + Sync {
+    // how hand histories name the game, such as "No Limit".
+    fn name(&self) -> &str;
+
+    // smallest and largest street totals a bet or raise may go to.
+    fn raise_bounds(&self, spot: &RaiseSpot) -> (u64, u64);
+
+    // bets and raises allowed on a street, the big blind counting as the first one preflop.
+    fn raise_cap(&self) -> Option<usize> {
+        None
+    }
+}
+
+// a shared structure, such as one kept in a configuration, sizes bets like the one it holds.
+impl<S: BettingStructure + ?Sized> BettingStructure for Arc<S> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn raise_bounds(&self, spot: &RaiseSpot) -> (u64, u64) {
+        (**self).raise_bounds(spot)
+    }
+
+    fn raise_cap(&self) -> Option<usize> {
+        (**self).raise_cap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoLimit;
+
+impl BettingStructure for NoLimit {
+    fn name(&self) -> &str {
+        "No Limit"
+    }
+
+    fn raise_bounds(&self, spot: &RaiseSpot) -> (u64, u64) {
+        (spot.min_raise_to(), u64::MAX)
+    }
+}
+
+// a raise goes at most to the size of the pot once the raiser has called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PotLimit;
+
+impl BettingStructure for PotLimit {
+    fn name(&self) -> &str {
+        "Pot Limit"
+    }
+
+    fn raise_bounds(&self, spot: &RaiseSpot) -> (u64, u64) {
+        let call = spot.current_bet - spot.street_bet;
+        (spot.min_raise_to(), spot.current_bet + spot.pot + call)
+    }
+}
+
+// bets and raises of the small bet preflop and on the flop, of the big bet on the turn
+// and river, up to `cap` of them a street.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedLimit {
+    pub small_bet: u64,
+    pub big_bet: u64,
+    pub cap: usize,
+}
+
+impl FixedLimit {
+    pub fn new(small_bet: u64, big_bet: u64) -> Self {
+        Self { small_bet, big_bet, cap: 4 }
+    }
+
+    pub fn with_cap(mut self, cap: usize) -> Self {
+        self.cap = cap;
+        self
+    }
+}
+
+impl BettingStructure for FixedLimit {
+    fn name(&self) -> &str {
+        "Limit"
+    }
+
+    fn raise_bounds(&self, spot: &RaiseSpot) -> (u64, u64) {
+        let bet = match spot.stage {
+            Stage::PreFlop | Stage::Flop => self.small_bet,
+            Stage::Turn | Stage::River => self.big_bet,
+        };
+        (spot.current_bet + bet, spot.current_bet + bet)
+    }
+
+    fn raise_cap(&self) -> Option<usize> {
+        Some(self.cap)
+    }
+}
+
+// one hand of hold'em on a HoldemGame, from the blinds to the award of the pot.
+#[derive(Debug, Clone)]
+pub struct HoldemTable {
+    game: HoldemGame,
+    blinds: Blinds,
+    button: usize,
+    starting_stacks: Vec<u64>,
+    stacks: Vec<u64>,
+    street_bets: Vec<u64>,
+    contributions: Vec<u64>,
+    folded: Vec<bool>,
+    // the bet a seat last acted at on this street.
+    acted_at: Vec<Option<u64>>,
+    current_bet: u64,
+    min_raise: u64,
+    // the bet after the last full raise. a short all-in raise does not reopen the betting.
+    last_full_bet: u64,
+    to_act: Option<usize>,
+    history: Vec<ActionRecord>,
+    winnings: Vec<u64>,
+    odd_chip_rule: OddChipRule,
+    structure: Arc<dyn BettingStructure>,
+    // bets and raises made on the street.
+    raises: usize,
+}
+
+impl HoldemTable {
+    // deals the game's hole cards and posts antes and blinds. seats given known hole cards
+    // on the game keep them.
+    pub fn new(game: HoldemGame, stacks: Vec<u64>, button: usize, blinds: Blinds) -> Result<Self, &'static str> {
+        Self::with_rules(game, stacks, button, blinds, OddChipRule::default())
+    }
+
+    pub fn with_rules(
+        game: HoldemGame,
+        stacks: Vec<u64>,
+        button: usize,
+        blinds: Blinds,
+        odd_chip_rule: OddChipRule,
+    ) -> Result<Self, &'static str> {
+        Self::with_structure(game, stacks, button, blinds, odd_chip_rule, NoLimit)
+    }
+
+    pub fn with_structure(
+        game: HoldemGame,
+        stacks: Vec<u64>,
+        button: usize,
+        blinds: Blinds,
+        odd_chip_rule: OddChipRule,
+        structure: impl BettingStructure + 'static,
+    ) -> Result<Self, &'static str> {
+        let players_cnt = game.players_cnt();
+        if players_cnt < 2 {return Err("A hand needs at least 2 players.");}
+        if stacks.len() != players_cnt {return Err("Stacks must match the seats.");}
+        if stacks.contains(&0) {return Err("Every seat needs chips.");}
+        if button >= players_cnt {return Err("No such seat for the button.");}
+        if blinds.big_blind == 0 || blinds.small_blind > blinds.big_blind {return Err("Invalid blinds.");}
+        if !game.board_cards().is_empty() {return Err("The hand must start before the flop.");}
+        let mut table = Self {
+            game,
+            blinds,
+            button,
+            starting_stacks: stacks.clone(),
+            stacks,
+            street_bets: vec![0; players_cnt],
+            contributions: vec![0; players_cnt],
+            folded: vec![false; players_cnt],
+            acted_at: vec![None; players_cnt],
+            current_bet: blinds.big_blind,
+            min_raise: blinds.big_blind,
+            last_full_bet: blinds.big_blind,
+            to_act: None,
+            history: Vec::new(),
+            winnings: vec![0; players_cnt],
+            odd_chip_rule,
+            structure: Arc::new(structure),
+            raises: 1,
+        };
+        table.game.deal_hole_cards();
+        for seat in 0..players_cnt {
+            let ante = blinds.ante.min(table.stacks[seat]);
+            table.stacks[seat] -= ante;
+            table.contributions[seat] += ante;
+        }
+        let (small_blind, big_blind) = table.blind_seats();
+        table.put_in(small_blind, blinds.small_blind);
+        table.put_in(big_blind, blinds.big_blind);
+        table.advance(big_blind);
+        Ok(table)
+    }
+
+    // heads-up, the button posts the small blind.
+    pub fn blind_seats(&self) -> (usize, usize) {
+        let n = self.seats();
+        match n {
+            2 => (self.button, (self.button + 1) % n),
+            _ => ((self.button + 1) % n, (self.button + 2) % n),
+        }
+    }
+
+    pub fn seats(&self) -> usize {
+        self.stacks.len()
+    }
+
+    pub fn game(&self) -> &HoldemGame {
+        &self.game
+    }
+
+    pub fn blinds(&self) -> Blinds {
+        self.blinds
+    }
+
+    pub fn structure(&self) -> &dyn BettingStructure {
+        self.structure.as_ref()
+    }
+
+    pub fn odd_chip_rule(&self) -> OddChipRule {
+        self.odd_chip_rule
+    }
+
+    pub fn button(&self) -> usize {
+        self.button
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.game.stage()
+    }
+
+    pub fn starting_stacks(&self) -> &[u64] {
+        &self.starting_stacks
+    }
+
+    pub fn stacks(&self) -> &[u64] {
+        &self.stacks
+    }
+
+    // chips put in on the current street.
+    pub fn street_bets(&self) -> &[u64] {
+        &self.street_bets
+    }
+
+    // chips put in during the whole hand, antes included.
+    pub fn contributions(&self) -> &[u64] {
+        &self.contributions
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.contributions.iter().sum()
+    }
+
+    // the main pot and the side pots as they stand.
+    pub fn pots(&self) -> Vec<Pot> {
+        build_pots(&self.contributions, &self.folded)
+    }
+
+    pub fn current_bet(&self) -> u64 {
+        self.current_bet
+    }
+
+    pub fn is_folded(&self, seat: usize) -> bool {
+        self.folded[seat]
+    }
+
+    pub fn is_all_in(&self, seat: usize) -> bool {
+        !self.folded[seat] && self.stacks[seat] == 0
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.to_act.is_none()
+    }
+
+    pub fn history(&self) -> &[ActionRecord] {
+        &self.history
+    }
+
+    // chips each seat took from the pot once the hand is over.
+    pub fn winnings(&self) -> &[u64] {
+        &self.winnings
+    }
+
+    // chips won or lost by each seat over the hand.
+    pub fn payoffs(&self) -> Vec<i64> {
+        self.stacks.iter().zip(&self.starting_stacks).map(|(&stack, &start)| stack as i64 - start as i64).collect()
+    }
+
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        let seat = self.to_act?;
+        let street_bet = self.street_bets[seat];
+        let all_in_to = street_bet + self.stacks[seat];
+        let call_amount = (self.current_bet - street_bet).min(self.stacks[seat]);
+        let reopened = self.acted_at[seat].is_none_or(|bet| self.last_full_bet > bet);
+        let someone_can_call = (0..self.seats()).any(|other| other != seat && !self.folded[other] && self.stacks[other] > 0);
+        let capped = self.structure.raise_cap().is_some_and(|cap| self.raises >= cap);
+        let (min_raise_to, max_raise_to) = self.structure.raise_bounds(&RaiseSpot {
+            stage: self.stage(),
+            big_blind: self.blinds.big_blind,
+            current_bet: self.current_bet,
+            min_raise: self.min_raise,
+            street_bet,
+            pot: self.pot(),
+        });
+        Some(LegalActions {
+            current_bet: self.current_bet,
+            call_amount,
+            can_check: street_bet == self.current_bet,
+            can_raise: all_in_to > self.current_bet && reopened && someone_can_call && !capped,
+            min_raise_to: min_raise_to.min(all_in_to),
+            max_raise_to: max_raise_to.min(all_in_to),
+        })
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), &'static str> {
+        let seat = self.to_act.ok_or("The hand is over.")?;
+        let legal = self.legal_actions().ok_or("The hand is over.")?;
+        legal.check(action)?;
+        let amount = match action {
+            Action::Fold => {
+                self.folded[seat] = true;
+                0
+            }
+            Action::Check => 0,
+            Action::Call => self.put_in(seat, legal.call_amount),
+            Action::Bet(to) | Action::Raise(to) => {
+                let raise = to - self.current_bet;
+                if raise >= self.min_raise {
+                    self.min_raise = raise;
+                    self.last_full_bet = to;
+                }
+                self.current_bet = to;
+                self.raises += 1;
+                self.put_in(seat, to - self.street_bets[seat])
+            }
+        };
+        self.acted_at[seat] = Some(self.current_bet);
+        self.history.push(ActionRecord { stage: self.stage(), seat, action, amount });
+        self.advance(seat);
+        Ok(())
+    }
+
+    fn put_in(&mut self, seat: usize, amount: u64) -> u64 {
+        let amount = amount.min(self.stacks[seat]);
+        self.stacks[seat] -= amount;
+        self.street_bets[seat] += amount;
+        self.contributions[seat] += amount;
+        amount
+    }
+
+    fn needs_action(&self, seat: usize) -> bool {
+        if self.folded[seat] || self.stacks[seat] == 0 {
+            return false;
+        }
+        let can_act = (0..self.seats()).filter(|&other| !self.folded[other] && self.stacks[other] > 0).count();
+        self.street_bets[seat] < self.current_bet || (self.acted_at[seat].is_none() && can_act >= 2)
+    }
+
+    // the first seat after `seat` that still has to act on this street.
+    fn next_to_act(&self, seat: usize) -> Option<usize> {
+        let n = self.seats();
+        (1..=n).map(|step| (seat + step) % n).find(|&next| self.needs_action(next))
+    }
+
+    fn advance(&mut self, seat: usize) {
+        if self.folded.iter().filter(|&&folded| !folded).count() == 1 {
+            return self.award();
+        }
+        self.to_act = self.next_to_act(seat);
+        while self.to_act.is_none() {
+            // the betting round is closed.
+            if self.stage() == Stage::River {
+                return self.award();
+            }
+            self.street_bets.iter_mut().for_each(|bet| *bet = 0);
+            self.acted_at.iter_mut().for_each(|acted_at| *acted_at = None);
+            self.current_bet = 0;
+            self.raises = 0;
+            self.min_raise = self.blinds.big_blind;
+            self.last_full_bet = 0;
+            self.game.next_stage();
+            self.to_act = self.next_to_act(self.button);
+        }
+    }
+
+    // every pot goes to the best grade among its eligible seats. a pot only one seat is
+    // eligible for, such as an uncalled bet, goes back to it without a showdown.
+    fn award(&mut self) {
+        self.to_act = None;
+        let showdown = self.folded.iter().filter(|&&folded| !folded).count() > 1;
+        let grades: Vec<Option<Grade>> = (0..self.seats())
+            .map(|seat| (showdown && !self.folded[seat]).then(|| Grade::from_set(self.game.players()[seat].card_set())))
+            .collect();
+        self.winnings = award_pots(&self.pots(), &grades, self.button, self.odd_chip_rule).expect("every seat left in has a grade");
+        self.stacks.iter_mut().zip(&self.winnings).for_each(|(stack, won)| *stack += won);
+    }
+}
+
+#[cfg(test)]
+mod test_for_betting {
+    use crate::betting::*;
+    use crate::deck::*;
+
+    fn table(stacks: Vec<u64>, button: usize, seed: u64) -> HoldemTable {
+        let game = HoldemGame::with_seed(stacks.len(), seed);
+        HoldemTable::new(game, stacks, button, Blinds::new(1, 2)).unwrap()
+    }
+
+    #[test]
+    fn blinds_and_first_to_act() {
+        let heads_up = table(vec![100, 100], 0, 1);
+        assert_eq!(heads_up.blind_seats(), (0, 1));
+        assert_eq!(heads_up.street_bets(), &[1, 2]);
+        assert_eq!(heads_up.to_act(), Some(0));
+        let three_handed = table(vec![100, 100, 100], 0, 1);
+        assert_eq!(three_handed.street_bets(), &[0, 1, 2]);
+        assert_eq!(three_handed.to_act(), Some(0));
+        assert_eq!(three_handed.pot(), 3);
+        let mut with_ante = HoldemTable::new(HoldemGame::with_seed(3, 1), vec![100, 100, 1], 2, Blinds::new(1, 2).with_ante(1)).unwrap();
+        assert_eq!(with_ante.contributions(), &[2, 3, 1]);
+        assert!(with_ante.is_all_in(2));
+        assert_eq!(with_ante.to_act(), Some(0));
+        with_ante.act(Action::Call).unwrap();
+        // the big blind still has the option.
+        assert_eq!(with_ante.to_act(), Some(1));
+        assert!(with_ante.legal_actions().unwrap().can_check);
+    }
+
+    #[test]
+    fn min_raise_rules() {
+        let mut table = table(vec![100, 100, 100], 0, 1);
+        let legal = table.legal_actions().unwrap();
+        assert_eq!((legal.call_amount, legal.min_raise_to, legal.max_raise_to), (2, 4, 100));
+        assert!(table.act(Action::Check).is_err());
+        assert!(table.act(Action::Bet(4)).is_err());
+        assert!(table.act(Action::Raise(3)).is_err());
+        table.act(Action::Raise(7)).unwrap();
+        // the next raise must be at least as large as the last one.
+        assert_eq!(table.legal_actions().unwrap().min_raise_to, 12);
+        assert!(table.act(Action::Raise(11)).is_err());
+        table.act(Action::Raise(12)).unwrap();
+        table.act(Action::Fold).unwrap();
+        table.act(Action::Call).unwrap();
+        assert_eq!(table.stage(), Stage::Flop);
+        assert_eq!(table.pot(), 26);
+        // the first player left of the button opens the flop.
+        assert_eq!(table.to_act(), Some(1));
+        assert!(table.act(Action::Call).is_err());
+        assert!(table.act(Action::Bet(1)).is_err());
+        table.act(Action::Bet(2)).unwrap();
+        assert_eq!(table.legal_actions().unwrap().min_raise_to, 4);
+    }
+
+    #[test]
+    fn folding_around_gives_the_blinds() {
+        let mut table = table(vec![100, 100, 100], 0, 1);
+        table.act(Action::Fold).unwrap();
+        table.act(Action::Fold).unwrap();
+        assert!(table.is_finished());
+        assert_eq!(table.stacks(), &[100, 99, 101]);
+        assert_eq!(table.payoffs(), vec![0, -1, 1]);
+        assert!(table.act(Action::Check).is_err());
+    }
+
+    #[test]
+    fn check_down_to_showdown() {
+        let mut game = HoldemGame::with_seed(2, 5);
+        game.set_hole_cards(0, parse_cards("As Ad").unwrap()).unwrap();
+        game.set_hole_cards(1, parse_cards("7c 2d").unwrap()).unwrap();
+        let mut table = HoldemTable::new(game, vec![50, 50], 1, Blinds::new(1, 2)).unwrap();
+        // the button is seat 1 : it completes the small blind and seat 0 checks behind.
+        table.act(Action::Call).unwrap();
+        table.act(Action::Check).unwrap();
+        while !table.is_finished() {
+            table.act(Action::Check).unwrap();
+        }
+        assert_eq!(table.stage(), Stage::River);
+        let grades: Vec<Grade> = table.game().players().iter().map(|hand| Grade::from_set(hand.card_set())).collect();
+        let payoffs = table.payoffs();
+        assert_eq!(payoffs.iter().sum::<i64>(), 0);
+        match grades[0].partial_cmp(&grades[1]).unwrap() {
+            std::cmp::Ordering::Greater => assert_eq!(payoffs, vec![2, -2]),
+            std::cmp::Ordering::Less => assert_eq!(payoffs, vec![-2, 2]),
+            std::cmp::Ordering::Equal => assert_eq!(payoffs, vec![0, 0]),
+        }
+        assert_eq!(table.history().len(), 2 + 3 * 2);
+    }
+
+    #[test]
+    fn short_all_in_does_not_reopen_betting() {
+        let mut table = table(vec![100, 100, 15], 0, 1);
+        // seat 0 raises to 10, seat 1 calls and seat 2 moves in for 15 : not a full raise.
+        table.act(Action::Raise(10)).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Raise(15)).unwrap();
+        assert!(table.is_all_in(2));
+        let legal = table.legal_actions().unwrap();
+        assert_eq!(table.to_act(), Some(0));
+        assert!(!legal.can_raise);
+        assert_eq!(legal.call_amount, 5);
+        table.act(Action::Call).unwrap();
+        table.act(Action::Call).unwrap();
+        assert_eq!(table.stage(), Stage::Flop);
+        assert_eq!(table.pot(), 45);
+    }
+
+    #[test]
+    fn all_in_runs_the_board_out() {
+        let mut table = table(vec![100, 40], 0, 3);
+        table.act(Action::Raise(100)).unwrap();
+        // all-in for 100 against a stack of 40 : 60 are never called.
+        table.act(Action::Call).unwrap();
+        assert!(table.is_finished());
+        assert_eq!(table.game().board_cards().len(), 5);
+        assert_eq!(table.pot(), 140);
+        let winnings = table.winnings();
+        assert_eq!(winnings.iter().sum::<u64>(), 140);
+        assert!(winnings[0] >= 60);
+        assert_eq!(table.stacks().iter().sum::<u64>(), 140);
+    }
+
+    #[test]
+    fn all_ins_build_side_pots() {
+        let mut table = table(vec![50, 100, 200], 0, 7);
+        table.act(Action::Raise(50)).unwrap();
+        table.act(Action::Raise(100)).unwrap();
+        // nobody is left to call a raise.
+        assert!(table.act(Action::Raise(200)).is_err());
+        table.act(Action::Call).unwrap();
+        assert!(table.is_finished());
+        let pots = table.pots();
+        assert_eq!(pots.iter().map(|pot| pot.amount).collect::<Vec<u64>>(), vec![150, 100]);
+        assert_eq!(pots[1].eligible, vec![1, 2]);
+        let winnings = table.winnings();
+        assert_eq!(winnings.iter().sum::<u64>(), 250);
+        assert!(winnings[0] <= 150 && winnings[1] <= 250);
+        assert_eq!(table.stacks().iter().sum::<u64>(), 350);
+    }
+
+    #[test]
+    fn odd_chip_rule_of_the_table() {
+        let mut game = HoldemGame::with_seed(3, 2);
+        game.set_hole_cards(0, parse_cards("2c 3c").unwrap()).unwrap();
+        game.set_hole_cards(2, parse_cards("2d 3d").unwrap()).unwrap();
+        let mut table = HoldemTable::with_rules(game, vec![100, 100, 100], 0, Blinds::new(1, 2), OddChipRule::LowestSeat).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Fold).unwrap();
+        table.act(Action::Check).unwrap();
+        while !table.is_finished() {
+            table.act(Action::Check).unwrap();
+        }
+        // both players hold the same ranks, so the board decides whether they split the 5 chips.
+        let winnings = table.winnings();
+        assert_eq!(winnings.iter().sum::<u64>(), 5);
+        if winnings[0] > 0 && winnings[2] > 0 {
+            assert_eq!(winnings, &[3, 0, 2]);
+        }
+    }
+
+    #[test]
+    fn fixed_limit_sizes_and_cap() {
+        let game = HoldemGame::with_seed(3, 1);
+        let structure = FixedLimit::new(2, 4);
+        let mut table = HoldemTable::with_structure(game, vec![100; 3], 0, Blinds::new(1, 2), OddChipRule::default(), structure).unwrap();
+        let legal = table.legal_actions().unwrap();
+        assert_eq!((legal.min_raise_to, legal.max_raise_to), (4, 4));
+        assert!(table.act(Action::Raise(6)).is_err());
+        table.act(Action::Raise(4)).unwrap();
+        table.act(Action::Raise(6)).unwrap();
+        table.act(Action::Raise(8)).unwrap();
+        // the big blind, a raise and two re-raises make the cap of 4.
+        let legal = table.legal_actions().unwrap();
+        assert!(!legal.can_raise);
+        assert!(table.act(Action::Raise(10)).is_err());
+        table.act(Action::Call).unwrap();
+        table.act(Action::Call).unwrap();
+        assert_eq!(table.stage(), Stage::Flop);
+        table.act(Action::Bet(2)).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Call).unwrap();
+        assert_eq!(table.stage(), Stage::Turn);
+        let legal = table.legal_actions().unwrap();
+        assert_eq!((legal.min_raise_to, legal.max_raise_to), (4, 4));
+        table.act(Action::Bet(4)).unwrap();
+        assert_eq!(table.legal_actions().unwrap().min_raise_to, 8);
+    }
+
+    #[test]
+    fn pot_limit_sizes() {
+        let game = HoldemGame::with_seed(3, 1);
+        let mut table = HoldemTable::with_structure(game, vec![100; 3], 0, Blinds::new(1, 2), OddChipRule::default(), PotLimit).unwrap();
+        // 3 in the pot : calling 2 makes 5, so the pot raise goes to 2 + 5.
+        let legal = table.legal_actions().unwrap();
+        assert_eq!((legal.min_raise_to, legal.max_raise_to), (4, 7));
+        assert!(table.act(Action::Raise(8)).is_err());
+        table.act(Action::Raise(7)).unwrap();
+        // 10 in the pot, the small blind calls 6 more : 16, raised to 7 + 16.
+        assert_eq!(table.legal_actions().unwrap().max_raise_to, 23);
+        table.act(Action::Call).unwrap();
+        table.act(Action::Call).unwrap();
+        let legal = table.legal_actions().unwrap();
+        assert_eq!((legal.min_raise_to, legal.max_raise_to), (2, 21));
+        table.act(Action::Bet(21)).unwrap();
+        // 42 in the pot, calling 21 makes 63 : a raise to 84 at most.
+        assert_eq!(table.legal_actions().unwrap().max_raise_to, 84);
+        assert_eq!(table.legal_actions().unwrap().min_raise_to, 42);
+    }
+
+    #[test]
+    fn invalid_tables() {
+        let game = || HoldemGame::with_seed(2, 1);
+        assert!(HoldemTable::new(game(), vec![100], 0, Blinds::new(1, 2)).is_err());
+        assert!(HoldemTable::new(game(), vec![100, 0], 0, Blinds::new(1, 2)).is_err());
+        assert!(HoldemTable::new(game(), vec![100, 100], 2, Blinds::new(1, 2)).is_err());
+        assert!(HoldemTable::new(game(), vec![100, 100], 0, Blinds::new(2, 1)).is_err());
+        assert!(HoldemTable::new(HoldemGame::with_seed(1, 1), vec![100], 0, Blinds::new(1, 2)).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+use crate::deck::{Card, Suit};
+
+// a set of cards packed in a u64 : the bit of a card is its deck index (suit * 13 + rank),
+// so the 13 bits of each suit form that suit's rank mask.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CardSet(u64);
+
+pub struct CardSetIter(u64);
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet(0);
+    pub const FULL: CardSet = CardSet((1 << 52) - 1);
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        Self(bits & Self::FULL.0)
+    }
+
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub fn single(card: Card) -> Self {
+        Self(1 << card.index())
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.0 & (1 << card.index()) != 0
+    }
+
+    // returns false if the card was already in the set.
+    pub fn insert(&mut self, card: Card) -> bool {
+        let was_absent = !self.contains(card);
+        self.0 |= 1 << card.index();
+        was_absent
+    }
+
+    // returns false if the card was not in the set.
+    pub fn remove(&mut self, card: Card) -> bool {
+        let was_present = self.contains(card);
+        self.0 &= !(1 << card.index());
+        was_present
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    pub fn is_disjoint(self, other: Self) -> bool {
+        self.0 & other.0 == 0
+    }
+
+    pub fn is_subset(self, other: Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    // bit r is set when the card of rank r in the given suit is in the set.
+    pub fn suit_mask(self, suit: Suit) -> u16 {
+        ((self.0 >> (suit.index() * 13)) & 0x1FFF) as u16
+    }
+
+    // ranks present in any suit.
+    pub fn rank_mask(self) -> u16 {
+        Suit::ALL
+            .iter()
+            .fold(0, |mask, &suit| mask | self.suit_mask(suit))
+    }
+
+    pub fn iter(self) -> CardSetIter {
+        CardSetIter(self.0)
+    }
+
+    pub fn to_vec(self) -> Vec<Card> {
+        self.iter().collect()
+    }
+}
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Card::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|card| {
+            self.insert(card);
+        });
+    }
+}
+
+impl From<Card> for CardSet {
+    fn from(card: Card) -> Self {
+        Self::single(card)
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> Self {
+        cards.iter().collect()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for CardSet {
+    fn bitor_assign(&mut self, other: Self) {
+        *self = self.union(other);
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
+    }
+}
+
+impl BitAndAssign for CardSet {
+    fn bitand_assign(&mut self, other: Self) {
+        *self = self.intersection(other);
+    }
+}
+
+impl Sub for CardSet {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.difference(other)
+    }
+}
+
+impl SubAssign for CardSet {
+    fn sub_assign(&mut self, other: Self) {
+        *self = self.difference(other);
+    }
+}
+
+impl Not for CardSet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::FULL.difference(self)
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl fmt::Display for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.iter()
+                .map(|card| card.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_for_cardset {
+    use crate::cardset::*;
+    use crate::deck::*;
+
+    #[test]
+    fn insert_remove_and_count() {
+        let mut set = CardSet::new();
+        let ace = Card::new(Rank::Ace, Suit::Spade);
+        assert!(set.is_empty());
+        assert!(set.insert(ace));
+        assert!(!set.insert(ace));
+        assert!(set.contains(ace));
+        assert_eq!(set.len(), 1);
+        assert!(set.remove(ace));
+        assert!(!set.remove(ace));
+        assert_eq!(CardSet::FULL.len(), 52);
+        assert_eq!(Deck::new().cards.iter().collect::<CardSet>(), CardSet::FULL);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: CardSet = parse_cards("As Kd 7c").unwrap().iter().collect();
+        let b: CardSet = parse_cards("Kd 7c 2h").unwrap().iter().collect();
+        assert_eq!((a | b).len(), 4);
+        assert_eq!(a & b, parse_cards("Kd 7c").unwrap().iter().collect());
+        assert_eq!(a - b, CardSet::single(Card::new(Rank::Ace, Suit::Spade)));
+        assert_eq!((!a).len(), 49);
+        assert!((a - b).is_disjoint(b));
+        assert!((a & b).is_subset(a));
+    }
+
+    #[test]
+    fn iteration_follows_deck_index() {
+        let cards = parse_cards("2c As 3s Td").unwrap();
+        let set: CardSet = cards.iter().collect();
+        let mut expected = cards.clone();
+        expected.sort_by_key(|card| card.index());
+        assert_eq!(set.to_vec(), expected);
+        assert_eq!(set.iter().len(), 4);
+    }
+
+    #[test]
+    fn suit_and_rank_masks() {
+        let set: CardSet = parse_cards("As Ks 2s Ah 3c").unwrap().iter().collect();
+        assert_eq!(set.suit_mask(Suit::Spade), (1 << 12) | (1 << 11) | 1);
+        assert_eq!(set.suit_mask(Suit::Heart), 1 << 12);
+        assert_eq!(set.suit_mask(Suit::Diamond), 0);
+        assert_eq!(set.suit_mask(Suit::Club), 1 << 1);
+        assert_eq!(set.rank_mask(), (1 << 12) | (1 << 11) | (1 << 1) | 1);
+    }
+}
@@ -1,14 +1,42 @@
 extern crate rand;
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use std::cmp;
 use std::fmt;
+use std::str::FromStr;
+use crate::cardset::CardSet;
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, Hash)]
+#[derive(fmt::Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Spade,
+    Diamond,
+    Heart,
+    Club,
+}
+
+#[derive(fmt::Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+// cards are ordered by rank first, then by suit.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct Card {
-    pub shape: usize,
-    pub num: usize,
+    pub rank: Rank,
+    pub suit: Suit,
 }
 
 #[derive(fmt::Debug, Copy, Clone)]
@@ -17,21 +45,40 @@ pub struct Deck {
     pub cursor: usize,
 }
 
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        self.num.partial_cmp(&other.num)
+#[derive(fmt::Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    Empty,
+    InvalidToken(String),
+    InvalidRank(char),
+    InvalidSuit(char),
+    DuplicateCard(Card),
+    TooManyCards(usize),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no card given"),
+            Self::InvalidToken(token) => write!(f, "'{}' is not a card", token),
+            Self::InvalidRank(c) => write!(f, "'{}' is not a card rank", c),
+            Self::InvalidSuit(c) => write!(f, "'{}' is not a card suit", c),
+            Self::DuplicateCard(card) => write!(f, "{} appears more than once", card),
+            Self::TooManyCards(cnt) => write!(f, "{} cards cannot be held in a hand", cnt),
+        }
     }
 }
 
+impl std::error::Error for ParseCardError {}
+
 impl fmt::Debug for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}{}", self.suit.to_char(), self.rank.to_char())
     }
 }
 
@@ -49,77 +96,253 @@ impl fmt::Display for Deck {
     }
 }
 
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spade, Suit::Diamond, Suit::Heart, Suit::Club];
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Spade => 'S',
+            Self::Diamond => 'D',
+            Self::Heart => 'H',
+            Self::Club => 'C',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'S' => Some(Self::Spade),
+            'D' => Some(Self::Diamond),
+            'H' => Some(Self::Heart),
+            'C' => Some(Self::Club),
+            _ => None,
+        }
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Ten => 'T',
+            Self::Jack => 'J',
+            Self::Queen => 'Q',
+            Self::King => 'K',
+            Self::Ace => 'A',
+            _ => char::from(b'2' + self as u8),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            '2'..='9' => Self::from_index(c as usize - '2' as usize),
+            'T' => Some(Self::Ten),
+            'J' => Some(Self::Jack),
+            'Q' => Some(Self::Queen),
+            'K' => Some(Self::King),
+            'A' => Some(Self::Ace),
+            _ => None,
+        }
+    }
+}
+
 impl Card {
-    fn shape_to_string(&self) -> String {
-        match self.shape {
-            0 => String::from("S"),
-            1 => String::from("D"),
-            2 => String::from("H"),
-            3 => String::from("C"),
-            _ => String::from("Not Existant Shape"),
+    pub const fn new(rank: Rank, suit: Suit) -> Self {
+        Self { rank, suit }
+    }
+
+    // a card's index is its position in a fresh deck : suit * 13 + rank.
+    pub fn from_index(index: usize) -> Option<Self> {
+        if index >= 52 {
+            return None;
+        }
+        Some(Self::new(Rank::ALL[index % 13], Suit::ALL[index / 13]))
+    }
+
+    pub fn index(self) -> usize {
+        self.suit.index() * 13 + self.rank.index()
+    }
+
+    // the card the way hand histories write it : rank first, then the suit in lower case.
+    pub fn to_text(self) -> String {
+        format!("{}{}", self.rank.to_char(), self.suit.to_char().to_ascii_lowercase())
+    }
+}
+
+impl TryFrom<usize> for Card {
+    type Error = usize;
+
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        Self::from_index(index).ok_or(index)
+    }
+}
+
+impl From<Card> for usize {
+    fn from(card: Card) -> usize {
+        card.index()
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    // accepts both rank-first ("As", "10d") and suit-first ("SA") notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token = s.trim();
+        let token = token.replacen("10", "T", 1);
+        let chars: Vec<char> = token.chars().collect();
+        match chars[..] {
+            [] => Err(ParseCardError::Empty),
+            [a, b] => match (Rank::from_char(a), Suit::from_char(b)) {
+                (Some(rank), Some(suit)) => Ok(Card::new(rank, suit)),
+                (Some(_), None) => Err(ParseCardError::InvalidSuit(b)),
+                (None, _) => match (Suit::from_char(a), Rank::from_char(b)) {
+                    (Some(suit), Some(rank)) => Ok(Card::new(rank, suit)),
+                    (Some(_), None) => Err(ParseCardError::InvalidRank(b)),
+                    (None, _) => Err(ParseCardError::InvalidRank(a)),
+                },
+            },
+            _ => Err(ParseCardError::InvalidToken(s.trim().to_string())),
         }
     }
+}
+
+impl TryFrom<&str> for Card {
+    type Error = ParseCardError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
 
-    fn num_to_string(&self) -> String {
-        match self.num {
-            0 => String::from("2"),
-            1 => String::from("3"),
-            2 => String::from("4"),
-            3 => String::from("5"),
-            4 => String::from("6"),
-            5 => String::from("7"),
-            6 => String::from("8"),
-            7 => String::from("9"),
-            8 => String::from("T"),
-            9 => String::from("J"),
-            10 => String::from("Q"),
-            11 => String::from("K"),
-            12 => String::from("A"),
-            _ => String::from("Not Exist Number"),
+// parses a list of cards such as "As Kd", "AsKd", "SA, DT" or "10h 9h".
+// the same card must not appear twice.
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    let mut cards: Vec<Card> = Vec::new();
+    for word in s.split(|c: char| c.is_whitespace() || c == ',') {
+        let chars: Vec<char> = word.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let width = if chars[i] == '1' && chars.get(i + 1) == Some(&'0') { 3 } else { 2 };
+            let end = cmp::min(i + width, chars.len());
+            let token: String = chars[i..end].iter().collect();
+            let card: Card = token.parse()?;
+            if cards.contains(&card) {
+                return Err(ParseCardError::DuplicateCard(card));
+            }
+            cards.push(card);
+            i = end;
         }
     }
+    if cards.is_empty() {
+        return Err(ParseCardError::Empty);
+    }
+    Ok(cards)
+}
 
-    fn to_string(&self) -> String {
-        self.shape_to_string() + &self.num_to_string()
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Deck {
     pub fn new() -> Self {
-        let mut cards = [Card { shape: 0, num: 0 }; 52];
+        let mut cards = [Card::new(Rank::Two, Suit::Spade); 52];
         let cursor: usize = 0;
-        for shape in 0..=3 {
-            for num in 0..=12 {
-                cards[shape * 13 + num] = Card { shape, num }
+        for suit in Suit::ALL {
+            for rank in Rank::ALL {
+                let card = Card::new(rank, suit);
+                cards[card.index()] = card;
             }
         }
         Self { cards, cursor }
     }
 
-    pub fn shuffle(&mut self) -> () {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng); // rand::seq::SliceRandom;
+    // a deck shuffled by the caller's rng, so that the same seed gives the same order.
+    pub fn with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut deck = Self::new();
+        deck.shuffle_with(rng);
+        deck
+    }
+
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut thread_rng());
+    }
+
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng); // rand::seq::SliceRandom;
     }
 
-    pub fn partial_shuffle(&mut self) -> () {
-	let mut rng = thread_rng();
-	self.cards[self.cursor..].shuffle(&mut rng);
+    pub fn partial_shuffle(&mut self) {
+	self.partial_shuffle_with(&mut thread_rng());
     }
 
+    // shuffles only the cards which are not dealt yet.
+    pub fn partial_shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+	self.cards[self.cursor..].shuffle(rng);
+    }
+
+    // deals the given cards first, in the given order.
     pub fn set_front_cards(&mut self, front_cards: Vec<Card>) -> Result<(), &str> {
 	if self.cursor >= 2 {return Err("Front Cards' Slot Already occupied.");}
-	let i_front_cards: Vec<usize> = self.cards.iter()
-		.enumerate()
-		.filter(|(_i, &card)| front_cards.contains(&card))
-		.map(|(i, _card)| i )
-		.collect::<_>();
-	self.cards.swap(0, i_front_cards[0]);
-	self.cards.swap(1, i_front_cards[1]);
-	/*i_front_cards.into_iter()
-		.enumerate()
-		.for_each(|(j, (i, &card))| {self.cards.swap(j, i);});*/
-	self.cursor = front_cards.len();
-	return Ok(());
+	let known: CardSet = front_cards.iter().collect();
+	if known.len() != front_cards.len() {return Err("Known cards contain a duplicate.");}
+	let start = self.cursor;
+	self.deal_known_cards(known)?;
+	self.cards[start..self.cursor].copy_from_slice(&front_cards);
+	Ok(())
+    }
+
+    // moves the given undealt cards to the cursor and deals them.
+    pub fn deal_known_cards(&mut self, known: CardSet) -> Result<Vec<Card>, &'static str> {
+	if !known.is_subset(self.remaining()) {return Err("Known cards are already dealt.");}
+	let start = self.cursor;
+	for i in self.cursor..self.cards.len() {
+		if known.contains(self.cards[i]) {
+			self.cards.swap(self.cursor, i);
+			self.cursor += 1;
+		}
+	}
+	Ok(self.cards[start..self.cursor].to_vec())
+    }
+
+    pub fn remaining(&self) -> CardSet {
+        self.cards[self.cursor..].iter().collect()
+    }
+
+    pub fn dealt(&self) -> CardSet {
+        self.cards[..self.cursor].iter().collect()
     }
 
     pub fn deal_cards(&mut self, num: usize) -> Vec<Card> {
@@ -140,7 +363,7 @@ mod test_for_deck {
         deck.shuffle();
         deck.cards
             .iter()
-            .for_each(|&card| check_mat[card.shape][card.num] = true);
+            .for_each(|&card| check_mat[card.suit.index()][card.rank.index()] = true);
         let mut tf_cnt: u8 = 0;
         for sub1 in check_mat {
             for sub2 in sub1 {
@@ -157,13 +380,13 @@ mod test_for_deck {
         let fc = deck.deal_cards(4);
 	deck.deal_cards(13);
 	let fc2 = deck.deal_cards(3);
-	assert_eq!(fc[0], Card{shape:0, num:0});
-	assert_eq!(fc[1], Card{shape:0, num:1});
-	assert_eq!(fc[2], Card{shape:0, num:2});
-	assert_eq!(fc[3], Card{shape:0, num:3});
-	assert_eq!(fc2[0], Card{shape:1, num:4});
-	assert_eq!(fc2[1], Card{shape:1, num:5});
-	assert_eq!(fc2[2], Card{shape:1, num:6});
+	assert_eq!(fc[0], Card::new(Rank::Two, Suit::Spade));
+	assert_eq!(fc[1], Card::new(Rank::Three, Suit::Spade));
+	assert_eq!(fc[2], Card::new(Rank::Four, Suit::Spade));
+	assert_eq!(fc[3], Card::new(Rank::Five, Suit::Spade));
+	assert_eq!(fc2[0], Card::new(Rank::Six, Suit::Diamond));
+	assert_eq!(fc2[1], Card::new(Rank::Seven, Suit::Diamond));
+	assert_eq!(fc2[2], Card::new(Rank::Eight, Suit::Diamond));
     }
     #[test]
     fn partial_shuffling_test() {
@@ -174,4 +397,97 @@ mod test_for_deck {
 	assert_eq!(cards, deck.cards[..7]);
 	assert_ne!(post_cards, deck.cards[7..14]);
     }
+    #[test]
+    fn seeded_shuffling_is_reproducible() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let deck1 = Deck::with_rng(&mut StdRng::seed_from_u64(7));
+        let deck2 = Deck::with_rng(&mut StdRng::seed_from_u64(7));
+        let deck3 = Deck::with_rng(&mut StdRng::seed_from_u64(8));
+        assert_eq!(deck1.cards, deck2.cards);
+        assert_ne!(deck1.cards, deck3.cards);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut deck4 = Deck::new();
+        let dealt = deck4.deal_cards(2);
+        deck4.partial_shuffle_with(&mut rng);
+        assert_eq!(dealt, deck4.cards[..2]);
+        let mut deck5 = Deck::new();
+        deck5.deal_cards(2);
+        deck5.partial_shuffle_with(&mut StdRng::seed_from_u64(7));
+        assert_eq!(deck4.cards, deck5.cards);
+    }
+    #[test]
+    fn dealing_known_cards() {
+        let mut deck = Deck::new();
+        deck.shuffle();
+        let known: CardSet = parse_cards("Ah Kh 2c").unwrap().iter().collect();
+        let dealt = deck.deal_known_cards(known).unwrap();
+        assert_eq!(dealt.iter().collect::<CardSet>(), known);
+        assert_eq!(deck.cursor, 3);
+        assert_eq!(deck.dealt(), known);
+        assert_eq!(deck.remaining(), !known);
+        assert!(deck.deal_known_cards(known).is_err());
+    }
+    #[test]
+    fn front_cards_keep_their_order() {
+        let mut deck = Deck::new();
+        let front = parse_cards("Kh As").unwrap();
+        deck.set_front_cards(front.clone()).unwrap();
+        assert_eq!(deck.cards[..2], front[..]);
+        assert_eq!(deck.cursor, 2);
+        assert!(Deck::new().set_front_cards(vec![front[0], front[0]]).is_err());
+    }
+    #[test]
+    fn card_index_round_trip() {
+        let deck = Deck::new();
+        for (i, &card) in deck.cards.iter().enumerate() {
+            assert_eq!(card.index(), i);
+            assert_eq!(Card::from_index(i), Some(card));
+            assert_eq!(Card::try_from(i), Ok(card));
+        }
+        assert_eq!(Card::from_index(52), None);
+        assert_eq!(Suit::from_index(4), None);
+        assert_eq!(Rank::from_index(13), None);
+    }
+    #[test]
+    fn card_order_is_rank_then_suit() {
+        let two_clubs = Card::new(Rank::Two, Suit::Club);
+        let ace_spades = Card::new(Rank::Ace, Suit::Spade);
+        let ace_hearts = Card::new(Rank::Ace, Suit::Heart);
+        assert!(two_clubs < ace_spades);
+        assert_eq!(two_clubs.cmp(&ace_spades), std::cmp::Ordering::Less);
+        assert!(ace_spades < ace_hearts);
+        let mut cards = vec![ace_hearts, two_clubs, ace_spades];
+        cards.sort();
+        assert_eq!(cards, vec![two_clubs, ace_spades, ace_hearts]);
+    }
+    #[test]
+    fn parse_single_card() {
+        assert_eq!("As".parse::<Card>(), Ok(Card::new(Rank::Ace, Suit::Spade)));
+        assert_eq!("SA".parse::<Card>(), Ok(Card::new(Rank::Ace, Suit::Spade)));
+        assert_eq!("td".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Diamond)));
+        assert_eq!("10h".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Heart)));
+        assert_eq!(Card::try_from("c2"), Ok(Card::new(Rank::Two, Suit::Club)));
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamond).to_string().parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Diamond)));
+        assert_eq!(Card::new(Rank::Ace, Suit::Spade).to_text(), "As");
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamond).to_text().parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Diamond)));
+        assert_eq!("Ax".parse::<Card>(), Err(ParseCardError::InvalidSuit('x')));
+        assert_eq!("S1".parse::<Card>(), Err(ParseCardError::InvalidRank('1')));
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+        assert!("AsK".parse::<Card>().is_err());
+    }
+    #[test]
+    fn parse_card_list() {
+        let expected = vec![Card::new(Rank::Ace, Suit::Spade), Card::new(Rank::King, Suit::Diamond)];
+        assert_eq!(parse_cards("As Kd"), Ok(expected.clone()));
+        assert_eq!(parse_cards("AsKd"), Ok(expected.clone()));
+        assert_eq!(parse_cards("SA, DK"), Ok(expected));
+        assert_eq!(parse_cards("10h9h").map(|cards| cards.len()), Ok(2));
+        assert_eq!(
+            parse_cards("As as"),
+            Err(ParseCardError::DuplicateCard(Card::new(Rank::Ace, Suit::Spade)))
+        );
+        assert_eq!(parse_cards(" "), Err(ParseCardError::Empty));
+    }
 }
@@ -0,0 +1,214 @@
+use std::ops::Range;
+use crate::betting::Action;
+use crate::env::DiscreteAction;
+use crate::holdem::Stage;
+use crate::observation::Observation;
+
+// bumped whenever the layout below changes.
+pub const ENCODING_VERSION: u32 = 1;
+
+const CARDS: usize = 52;
+const STAGES: usize = 4;
+const ACTION_KINDS: usize = 5;
+
+// flattens an observation into a fixed-size vector of f32 for neural networks.
+//
+// layout, version 1, for n = max_players and h = history_len :
+//   hole cards     52   one-hot by Card::index
+//   board          52   one-hot by Card::index
+//   street          4   one-hot : preflop, flop, turn, river
+//   position        n   one-hot : seats after the button, 0 being the button
+//   pot             1   in big blinds
+//   stacks          n   in big blinds
+//   street bets     n   in big blinds
+//   folded          n   1 for a folded seat
+//   action mask     6   the DiscreteAction mask
+//   history     h * (4 + n + 5 + 1)
+//       one slot per action, the oldest first : street one-hot, acting seat one-hot, kind
+//       one-hot (fold, check, call, bet, raise), chips put in by the action in big blinds.
+//       only the last h actions are kept and unused slots stay zero.
+//
+// per-seat sections start from the observing seat and go around the table, so the observer is
+// always first. seats beyond the table size stay zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoder {
+    pub max_players: usize,
+    pub history_len: usize,
+}
+
+impl Encoder {
+    pub fn new(max_players: usize, history_len: usize) -> Self {
+        Self { max_players, history_len }
+    }
+
+    pub fn version(&self) -> u32 {
+        ENCODING_VERSION
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout().last().map_or(0, |(_, section)| section.end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn history_slot(&self) -> usize {
+        STAGES + self.max_players + ACTION_KINDS + 1
+    }
+
+    // every section by name with its place in the vector.
+    pub fn layout(&self) -> Vec<(&'static str, Range<usize>)> {
+        let n = self.max_players;
+        let sizes = [
+            ("hole_cards", CARDS),
+            ("board", CARDS),
+            ("street", STAGES),
+            ("position", n),
+            ("pot", 1),
+            ("stacks", n),
+            ("street_bets", n),
+            ("folded", n),
+            ("action_mask", DiscreteAction::COUNT),
+            ("history", self.history_len * self.history_slot()),
+        ];
+        let mut start = 0;
+        sizes
+            .iter()
+            .map(|&(name, size)| {
+                start += size;
+                (name, start - size..start)
+            })
+            .collect()
+    }
+
+    pub fn encode(&self, obs: &Observation) -> Result<Vec<f32>, &'static str> {
+        let mut out = vec![0.0; self.len()];
+        self.encode_into(obs, &mut out)?;
+        Ok(out)
+    }
+
+    // writes into a slice of exactly len() values, such as a row of a batch.
+    pub fn encode_into(&self, obs: &Observation, out: &mut [f32]) -> Result<(), &'static str> {
+        let players = obs.stacks.len();
+        if players > self.max_players {return Err("More players than the encoder has room for.");}
+        if out.len() != self.len() {return Err("The output does not match the encoder length.");}
+        out.fill(0.0);
+        let big_blind = obs.blinds.big_blind as f32;
+        let relative = |seat: usize| (seat + players - obs.seat) % players;
+        let mut sections = self.layout().into_iter().map(|(_, section)| section);
+        let mut next = || sections.next().unwrap().start;
+
+        let hole_cards = next();
+        for card in obs.hole_cards.iter() {
+            out[hole_cards + card.index()] = 1.0;
+        }
+        let board = next();
+        for card in &obs.board {
+            out[board + card.index()] = 1.0;
+        }
+        let street = next();
+        out[street + stage_index(obs.stage)] = 1.0;
+        let position = next();
+        out[position + (obs.seat + players - obs.button) % players] = 1.0;
+        let pot = next();
+        out[pot] = obs.pot as f32 / big_blind;
+        let (stacks, street_bets, folded) = (next(), next(), next());
+        for seat in 0..players {
+            out[stacks + relative(seat)] = obs.stacks[seat] as f32 / big_blind;
+            out[street_bets + relative(seat)] = obs.street_bets[seat] as f32 / big_blind;
+            out[folded + relative(seat)] = if obs.folded[seat] { 1.0 } else { 0.0 };
+        }
+        let action_mask = next();
+        for (i, &legal) in obs.action_mask.iter().enumerate() {
+            out[action_mask + i] = if legal { 1.0 } else { 0.0 };
+        }
+        let history = next();
+        let skipped = obs.history.len().saturating_sub(self.history_len);
+        for (i, record) in obs.history[skipped..].iter().enumerate() {
+            let slot = history + i * self.history_slot();
+            let kind = slot + STAGES + self.max_players;
+            out[slot + stage_index(record.stage)] = 1.0;
+            out[slot + STAGES + relative(record.seat)] = 1.0;
+            out[kind + action_index(record.action)] = 1.0;
+            out[kind + ACTION_KINDS] = record.amount as f32 / big_blind;
+        }
+        Ok(())
+    }
+}
+
+fn stage_index(stage: Stage) -> usize {
+    match stage {
+        Stage::PreFlop => 0,
+        Stage::Flop => 1,
+        Stage::Turn => 2,
+        Stage::River => 3,
+    }
+}
+
+fn action_index(action: Action) -> usize {
+    match action {
+        Action::Fold => 0,
+        Action::Check => 1,
+        Action::Call => 2,
+        Action::Bet(_) => 3,
+        Action::Raise(_) => 4,
+    }
+}
+
+#[cfg(test)]
+mod test_for_encoding {
+    use crate::betting::*;
+    use crate::deck::*;
+    use crate::encoding::*;
+    use crate::holdem::HoldemGame;
+    use crate::observation::Observation;
+
+    fn section(encoder: &Encoder, encoded: &[f32], name: &str) -> Vec<f32> {
+        let (_, range) = encoder.layout().into_iter().find(|(section, _)| *section == name).unwrap();
+        encoded[range].to_vec()
+    }
+
+    #[test]
+    fn layout_is_stable() {
+        let encoder = Encoder::new(6, 8);
+        assert_eq!(encoder.version(), 1);
+        // 52 + 52 + 4 + 6 + 1 + 6 * 3 + 6 + 8 * (4 + 6 + 5 + 1)
+        assert_eq!(encoder.len(), 267);
+        let layout = encoder.layout();
+        assert_eq!(layout[0], ("hole_cards", 0..52));
+        assert_eq!(layout[4], ("pot", 114..115));
+        assert_eq!(layout.last().unwrap().1, 139..267);
+    }
+
+    #[test]
+    fn encodes_an_observation() {
+        let mut game = HoldemGame::with_seed(3, 1);
+        let holes = parse_cards("As Kd").unwrap();
+        game.set_hole_cards(1, holes.clone()).unwrap();
+        let mut table = HoldemTable::new(game, vec![100, 100, 100], 0, Blinds::new(1, 2)).unwrap();
+        table.act(Action::Raise(6)).unwrap();
+        let obs = Observation::new(&table, 1);
+        let encoder = Encoder::new(4, 2);
+        let encoded = encoder.encode(&obs).unwrap();
+        assert_eq!(encoded.len(), encoder.len());
+        let hole_cards = section(&encoder, &encoded, "hole_cards");
+        assert_eq!(hole_cards.iter().sum::<f32>(), 2.0);
+        assert!(holes.iter().all(|card| hole_cards[card.index()] == 1.0));
+        assert_eq!(section(&encoder, &encoded, "board"), vec![0.0; 52]);
+        assert_eq!(section(&encoder, &encoded, "street"), vec![1.0, 0.0, 0.0, 0.0]);
+        // the small blind sits one seat after the button.
+        assert_eq!(section(&encoder, &encoded, "position"), vec![0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(section(&encoder, &encoded, "pot"), vec![4.5]);
+        // seat 1 first, then 2 and 0, the fourth seat is empty.
+        assert_eq!(section(&encoder, &encoded, "street_bets"), vec![0.5, 1.0, 3.0, 0.0]);
+        assert_eq!(section(&encoder, &encoded, "stacks"), vec![49.5, 49.0, 47.0, 0.0]);
+        assert_eq!(section(&encoder, &encoded, "action_mask"), vec![1.0; 6]);
+        let history = section(&encoder, &encoded, "history");
+        // preflop, seat 0 two seats away, a raise putting in 3 big blinds.
+        assert_eq!(history[..14], [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 3.0]);
+        assert_eq!(history[14..], [0.0; 14]);
+        assert!(Encoder::new(2, 2).encode(&obs).is_err());
+        assert!(encoder.encode_into(&obs, &mut [0.0; 3]).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+use crate::betting::{Action, ActionRecord, BettingStructure, Blinds, HoldemTable, LegalActions, NoLimit};
+use crate::holdem::HoldemGame;
+use crate::observation::{Observation, OmniscientView};
+use crate::pot::OddChipRule;
+
+// the table an environment deals its hands on.
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub players: usize,
+    pub starting_stack: u64,
+    pub blinds: Blinds,
+    pub odd_chip_rule: OddChipRule,
+    pub structure: Arc<dyn BettingStructure>,
+}
+
+impl EnvConfig {
+    pub fn new(players: usize, starting_stack: u64, blinds: Blinds) -> Self {
+        Self { players, starting_stack, blinds, odd_chip_rule: OddChipRule::default(), structure: Arc::new(NoLimit) }
+    }
+
+    pub fn with_structure(mut self, structure: impl BettingStructure + 'static) -> Self {
+        self.structure = Arc::new(structure);
+        self
+    }
+
+    pub fn with_odd_chip_rule(mut self, odd_chip_rule: OddChipRule) -> Self {
+        self.odd_chip_rule = odd_chip_rule;
+        self
+    }
+
+    // a new hand with full stacks. the same seed deals the same cards.
+    pub fn deal(&self, button: usize, seed: u64) -> Result<HoldemTable, &'static str> {
+        HoldemTable::with_structure(
+            HoldemGame::with_seed(self.players, seed),
+            vec![self.starting_stack; self.players],
+            button,
+            self.blinds,
+            self.odd_chip_rule,
+            self.structure.clone(),
+        )
+    }
+}
+
+// a fixed set of choices for learning agents. raises are sized on the pot after calling and
+// kept within the legal bounds, so two sizes may name the same amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiscreteAction {
+    Fold,
+    CheckCall,
+    MinRaise,
+    HalfPot,
+    Pot,
+    AllIn,
+}
+
+impl DiscreteAction {
+    pub const COUNT: usize = 6;
+    pub const ALL: [DiscreteAction; DiscreteAction::COUNT] = [
+        DiscreteAction::Fold,
+        DiscreteAction::CheckCall,
+        DiscreteAction::MinRaise,
+        DiscreteAction::HalfPot,
+        DiscreteAction::Pot,
+        DiscreteAction::AllIn,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    // the table action this choice stands for. folding is left out when checking is free.
+    pub fn to_action(self, legal: &LegalActions, pot: u64) -> Option<Action> {
+        let raise_to = |to: u64| {
+            if !legal.can_raise {
+                return None;
+            }
+            let to = to.clamp(legal.min_raise_to, legal.max_raise_to);
+            Some(if legal.current_bet == 0 { Action::Bet(to) } else { Action::Raise(to) })
+        };
+        let pot_after_call = pot + legal.call_amount;
+        match self {
+            DiscreteAction::Fold if legal.can_check => None,
+            DiscreteAction::Fold => Some(Action::Fold),
+            DiscreteAction::CheckCall if legal.can_check => Some(Action::Check),
+            DiscreteAction::CheckCall => Some(Action::Call),
+            DiscreteAction::MinRaise => raise_to(legal.min_raise_to),
+            DiscreteAction::HalfPot => raise_to(legal.current_bet + pot_after_call / 2),
+            DiscreteAction::Pot => raise_to(legal.current_bet + pot_after_call),
+            DiscreteAction::AllIn => raise_to(legal.max_raise_to),
+        }
+    }
+}
+
+// what happened on a step, besides the new observation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepInfo {
+    pub record: ActionRecord,
+    // chips taken from the pot by each seat, all zero until the hand is over.
+    pub winnings: Vec<u64>,
+}
+
+// a reinforcement learning environment playing one hand per episode. stacks start full on
+// every hand and the button moves one seat each reset.
+#[derive(Debug, Clone)]
+pub struct HoldemEnv {
+    config: EnvConfig,
+    table: Option<HoldemTable>,
+    episodes: usize,
+}
+
+impl HoldemEnv {
+    pub fn new(config: EnvConfig) -> Result<Self, &'static str> {
+        let env = Self { config, table: None, episodes: 0 };
+        // check the configuration once with a throwaway hand.
+        env.config.deal(0, 0)?;
+        Ok(env)
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    // hands dealt so far.
+    pub fn episodes(&self) -> usize {
+        self.episodes
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let button = self.episodes % self.config.players;
+        let table = self.config.deal(button, seed).expect("the configuration was checked on creation");
+        self.table = Some(table);
+        self.episodes += 1;
+        self.observation()
+    }
+
+    // rewards are the chips each seat won or lost, paid when the hand is over.
+    pub fn step(&mut self, action: Action) -> Result<(Observation, Vec<f64>, bool, StepInfo), &'static str> {
+        let table = self.table.as_mut().ok_or("Reset the environment first.")?;
+        table.act(action)?;
+        let record = *table.history().last().unwrap();
+        let done = table.is_finished();
+        let rewards = match done {
+            true => table.payoffs().iter().map(|&payoff| payoff as f64).collect(),
+            false => vec![0.0; table.seats()],
+        };
+        let info = StepInfo { record, winnings: table.winnings().to_vec() };
+        Ok((self.observation(), rewards, done, info))
+    }
+
+    pub fn step_discrete(&mut self, action: DiscreteAction) -> Result<(Observation, Vec<f64>, bool, StepInfo), &'static str> {
+        let action = self.to_action(action).ok_or("The action is masked out.")?;
+        self.step(action)
+    }
+
+    // the table action a discrete choice stands for on this turn, none if it is masked out.
+    pub fn to_action(&self, action: DiscreteAction) -> Option<Action> {
+        let table = self.table.as_ref()?;
+        action.to_action(&table.legal_actions()?, table.pot())
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.table.as_ref().and_then(|table| table.to_act())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.to_act().is_none()
+    }
+
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        self.table.as_ref().and_then(|table| table.legal_actions())
+    }
+
+    pub fn action_mask(&self) -> [bool; DiscreteAction::COUNT] {
+        DiscreteAction::ALL.map(|action| self.to_action(action).is_some())
+    }
+
+    // what the seat to act sees. once the hand is over, the seat that acted last, or the
+    // button if nobody did.
+    pub fn observation(&self) -> Observation {
+        let table = self.table.as_ref().expect("Reset the environment first.");
+        let seat = table
+            .to_act()
+            .or_else(|| table.history().last().map(|record| record.seat))
+            .unwrap_or(table.button());
+        Observation::new(table, seat)
+    }
+
+    pub fn observe(&self, seat: usize) -> Observation {
+        Observation::new(self.table.as_ref().expect("Reset the environment first."), seat)
+    }
+
+    // the whole hand with every hole card, for logging only.
+    pub fn omniscient_view(&self) -> OmniscientView {
+        OmniscientView::new(self.table.as_ref().expect("Reset the environment first."))
+    }
+}
+
+#[cfg(test)]
+mod test_for_env {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use crate::betting::*;
+    use crate::env::*;
+    use crate::holdem::Stage;
+
+    fn env(players: usize) -> HoldemEnv {
+        HoldemEnv::new(EnvConfig::new(players, 200, Blinds::new(1, 2))).unwrap()
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        assert!(HoldemEnv::new(EnvConfig::new(1, 200, Blinds::new(1, 2))).is_err());
+        assert!(HoldemEnv::new(EnvConfig::new(3, 0, Blinds::new(1, 2))).is_err());
+        assert!(HoldemEnv::new(EnvConfig::new(3, 200, Blinds::new(2, 0))).is_err());
+    }
+
+    #[test]
+    fn reset_and_step() {
+        let mut env = env(3);
+        assert!(env.step(Action::Call).is_err());
+        let obs = env.reset(7);
+        assert_eq!((obs.to_act, obs.button, obs.stage), (Some(0), 0, Stage::PreFlop));
+        assert_eq!(obs.hole_cards.len(), 2);
+        assert!(obs.board.is_empty());
+        assert_eq!((obs.pot, obs.street_bets.clone()), (3, vec![0, 1, 2]));
+        assert_eq!(obs.action_mask, [true; DiscreteAction::COUNT]);
+        // an illegal action leaves the hand as it was.
+        assert!(env.step(Action::Check).is_err());
+        assert_eq!(env.to_act(), Some(0));
+        let (obs, rewards, done, info) = env.step(Action::Raise(6)).unwrap();
+        assert_eq!((obs.to_act, done), (Some(1), false));
+        assert_eq!(rewards, vec![0.0; 3]);
+        assert_eq!((info.record.seat, info.record.amount), (0, 6));
+        env.step(Action::Fold).unwrap();
+        let (obs, rewards, done, info) = env.step(Action::Fold).unwrap();
+        assert!(done && env.is_done());
+        assert_eq!((obs.seat, obs.to_act), (2, None));
+        assert_eq!(env.omniscient_view().winnings, vec![9, 0, 0]);
+        assert_eq!(obs.action_mask, [false; DiscreteAction::COUNT]);
+        assert_eq!(rewards, vec![3.0, -1.0, -2.0]);
+        assert_eq!(info.winnings, vec![9, 0, 0]);
+        assert!(env.step(Action::Call).is_err());
+        // the button moves with every hand.
+        assert_eq!(env.reset(7).button, 1);
+        assert_eq!(env.episodes(), 2);
+    }
+
+    #[test]
+    fn discrete_actions_follow_the_mask() {
+        let mut env = env(2);
+        env.reset(3);
+        env.step_discrete(DiscreteAction::CheckCall).unwrap();
+        // the big blind can check : folding is masked out.
+        let mask = env.action_mask();
+        assert!(!mask[DiscreteAction::Fold.index()] && mask[DiscreteAction::CheckCall.index()]);
+        assert!(env.step_discrete(DiscreteAction::Fold).is_err());
+        let legal = env.legal_actions().unwrap();
+        assert_eq!(DiscreteAction::Pot.to_action(&legal, 4), Some(Action::Raise(6)));
+        assert_eq!(DiscreteAction::AllIn.to_action(&legal, 4), Some(Action::Raise(200)));
+        env.step_discrete(DiscreteAction::AllIn).unwrap();
+        env.step_discrete(DiscreteAction::CheckCall).unwrap();
+        // both players are all-in and the board runs out.
+        assert!(env.is_done());
+        assert_eq!(env.observation().board.len(), 5);
+        assert_eq!(DiscreteAction::from_index(5), Some(DiscreteAction::AllIn));
+        assert_eq!(DiscreteAction::from_index(6), None);
+    }
+
+    #[test]
+    fn self_play_episodes() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for players in 2..=6 {
+            let mut env = env(players);
+            for episode in 0..200 {
+                let mut obs = env.reset(episode);
+                let mut steps = 0;
+                loop {
+                    let choices: Vec<DiscreteAction> =
+                        DiscreteAction::ALL.into_iter().filter(|action| obs.action_mask[action.index()]).collect();
+                    let action = *choices.choose(&mut rng).unwrap();
+                    let (next, rewards, done, _) = env.step_discrete(action).unwrap();
+                    steps += 1;
+                    if done {
+                        assert_eq!(rewards.iter().sum::<f64>(), 0.0);
+                        assert_eq!(next.stacks.iter().sum::<u64>(), 200 * players as u64);
+                        break;
+                    }
+                    assert_eq!(next.to_act, env.to_act());
+                    obs = next;
+                }
+                assert!(steps < 100);
+            }
+        }
+    }
+}
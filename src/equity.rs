@@ -0,0 +1,751 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::cardset::CardSet;
+use crate::deck::Card;
+use crate::evaluator::{evaluate, HandRank};
+use crate::range::Combo;
+
+// runouts drawn when a MonteCarloConfig sets no other limit.
+pub const DEFAULT_ITERATIONS: usize = 50000;
+// showdowns EquityMode::Auto is willing to enumerate exactly.
+pub const DEFAULT_EXACT_LIMIT: u128 = 2_000_000;
+// the stopping rules of a simulation are checked once per this many runouts.
+pub const CHECK_INTERVAL: usize = 1000;
+
+// split pots are counted in 1/2520 of a pot, which divides evenly among up to 10 players.
+const POT_UNITS: u64 = 2520;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EquityMode {
+    // every remaining board and every hole-card combination of the unknown seats.
+    Exact,
+    // random runouts until one of the config's limits is reached.
+    MonteCarlo(MonteCarloConfig),
+    // exact when at most exact_limit showdowns remain, else random runouts.
+    Auto {
+        exact_limit: u128,
+        montecarlo: MonteCarloConfig,
+    },
+}
+
+impl EquityMode {
+    // the simulation to run, or None for exact enumeration.
+    pub(crate) fn montecarlo_config(self, exact_showdowns: u128) -> Option<MonteCarloConfig> {
+        match self {
+            Self::Exact => None,
+            Self::MonteCarlo(config) => Some(config),
+            Self::Auto { exact_limit, montecarlo } => (exact_showdowns > exact_limit).then_some(montecarlo),
+        }
+    }
+}
+
+impl Default for EquityMode {
+    fn default() -> Self {
+        Self::Auto {
+            exact_limit: DEFAULT_EXACT_LIMIT,
+            montecarlo: MonteCarloConfig::default(),
+        }
+    }
+}
+
+// a simulation stops at max_iterations runouts, or earlier once the standard error of
+// the equity is at most target_std_error or time_limit has passed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonteCarloConfig {
+    pub max_iterations: usize,
+    pub target_std_error: Option<f64>,
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_ITERATIONS)
+    }
+}
+
+impl MonteCarloConfig {
+    pub fn new(max_iterations: usize) -> Self {
+        Self {
+            max_iterations,
+            target_std_error: None,
+            time_limit: None,
+        }
+    }
+
+    pub fn with_target_std_error(mut self, target_std_error: f64) -> Self {
+        self.target_std_error = Some(target_std_error);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    // whether a simulation started at `start` can stop with the given tallies, one per seat.
+    // the target standard error has to be reached by every seat.
+    pub(crate) fn is_done(&self, tallies: &[Tally], start: Instant) -> bool {
+        let samples = tallies[0].showdowns() as usize;
+        if samples >= self.max_iterations {
+            return true;
+        }
+        if let Some(target) = self.target_std_error {
+            let std_error = tallies.iter().map(Tally::std_error).fold(0.0, f64::max);
+            if samples >= CHECK_INTERVAL && std_error <= target {
+                return true;
+            }
+        }
+        matches!(self.time_limit, Some(limit) if start.elapsed() >= limit)
+    }
+}
+
+// showdowns won outright, split and lost, and the share of the pot won on average.
+// an exact result has no standard error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equity {
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+    pub samples: u64,
+    pub equity: f64,
+    pub std_error: f64,
+    pub exact: bool,
+}
+
+impl Equity {
+    pub fn win(&self) -> f64 {
+        self.wins as f64 / self.samples.max(1) as f64
+    }
+
+    pub fn tie(&self) -> f64 {
+        self.ties as f64 / self.samples.max(1) as f64
+    }
+
+    pub fn loss(&self) -> f64 {
+        self.losses as f64 / self.samples.max(1) as f64
+    }
+
+    // equity +- z standard errors, e.g. z = 1.96 for 95%.
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let margin = z * self.std_error;
+        ((self.equity - margin).max(0.0), (self.equity + margin).min(1.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Tally {
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+    pub pot_units: u64,
+    pub pot_units_sq: u64,
+}
+
+impl Tally {
+    // records one showdown for every seat, ranks[i] being the hand of seat i.
+    pub fn record_all(tallies: &mut [Tally], ranks: &[HandRank]) {
+        let best = ranks.iter().copied().max();
+        let sharing = ranks.iter().filter(|&&rank| Some(rank) == best).count() as u64;
+        for (tally, &rank) in tallies.iter_mut().zip(ranks) {
+            tally.record_share(if Some(rank) == best { sharing } else { 0 });
+        }
+    }
+
+    // records one showdown for the given seat only.
+    pub fn record_seat(&mut self, ranks: &[HandRank], seat: usize) {
+        let best = ranks.iter().copied().max();
+        let sharing = ranks.iter().filter(|&&rank| Some(rank) == best).count() as u64;
+        self.record_share(if Some(ranks[seat]) == best { sharing } else { 0 });
+    }
+
+    // records a pot split among `sharing` players, or lost when sharing is 0.
+    fn record_share(&mut self, sharing: u64) {
+        let units = match sharing {
+            0 => {
+                self.losses += 1;
+                0
+            }
+            1 => {
+                self.wins += 1;
+                POT_UNITS
+            }
+            _ => {
+                self.ties += 1;
+                POT_UNITS / sharing
+            }
+        };
+        self.pot_units += units;
+        self.pot_units_sq += units * units;
+    }
+
+    pub fn merge_all(tallies: &mut [Tally], others: &[Tally]) {
+        tallies.iter_mut().zip(others).for_each(|(tally, other)| tally.merge(other));
+    }
+
+    pub fn merge(&mut self, other: &Tally) {
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.losses += other.losses;
+        self.pot_units += other.pot_units;
+        self.pot_units_sq += other.pot_units_sq;
+    }
+
+    pub fn showdowns(&self) -> u64 {
+        self.wins + self.ties + self.losses
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.pot_units as f64 / POT_UNITS as f64 / self.showdowns().max(1) as f64
+    }
+
+    // standard error of the mean pot share.
+    pub fn std_error(&self) -> f64 {
+        let n = self.showdowns() as f64;
+        if n < 2.0 {
+            return f64::INFINITY;
+        }
+        let mean = self.equity();
+        let mean_sq = self.pot_units_sq as f64 / (POT_UNITS * POT_UNITS) as f64 / n;
+        let variance = (mean_sq - mean * mean).max(0.0) * n / (n - 1.0);
+        (variance / n).sqrt()
+    }
+
+    pub fn to_equity(self, exact: bool) -> Equity {
+        Equity {
+            wins: self.wins,
+            ties: self.ties,
+            losses: self.losses,
+            samples: self.showdowns(),
+            equity: self.equity(),
+            std_error: if exact { 0.0 } else { self.std_error() },
+            exact,
+        }
+    }
+}
+
+fn combinations(n: usize, k: usize) -> u128 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+}
+
+// the cards known before a showdown : the board so far, cards out of play and each
+// seat's hole cards. every seat gets its own tally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Spot {
+    pub board: CardSet,
+    pub dead: CardSet,
+    pub holes: Vec<CardSet>,
+}
+
+impl Spot {
+    fn known(&self) -> CardSet {
+        self.holes.iter().fold(self.board | self.dead, |known, &hole| known | hole)
+    }
+
+    // whether the deck still holds the cards every seat and the board are missing.
+    pub fn has_cards_left(&self) -> bool {
+        let missing = 5 - self.board.len() + self.holes.iter().map(|hole| 2 - hole.len()).sum::<usize>();
+        52 - self.known().len() >= missing
+    }
+
+    // number of showdowns an exact enumeration walks through.
+    pub fn exact_showdowns(&self) -> u128 {
+        let mut left = 52 - self.known().len();
+        let mut cnt = combinations(left, 5 - self.board.len());
+        left -= 5 - self.board.len();
+        for hole in &self.holes {
+            cnt *= combinations(left, 2 - hole.len());
+            left -= 2 - hole.len();
+        }
+        cnt
+    }
+
+    // tallies every showdown, filling in the unknown cards every possible way.
+    // the runouts are split by their lowest card, one unit of work each.
+    pub fn enumerate(&self, threads: usize) -> Vec<Tally> {
+        let known = self.known();
+        let remaining = (!known).to_vec();
+        let missing = 5 - self.board.len();
+        let units = if missing == 0 { 1 } else { remaining.len() };
+        let mut total = vec![Tally::default(); self.holes.len()];
+        run_ordered(
+            threads,
+            units,
+            |unit| {
+                let mut tallies = vec![Tally::default(); self.holes.len()];
+                let mut ranks: Vec<HandRank> = Vec::with_capacity(self.holes.len());
+                let (first, rest) = match missing {
+                    0 => (CardSet::EMPTY, &remaining[..0]),
+                    _ => (CardSet::single(remaining[unit]), &remaining[unit + 1..]),
+                };
+                for_each_subset(rest, missing.saturating_sub(1), first, &mut |runout| {
+                    self.deal_seats(self.board | runout, known | runout, &mut ranks, &mut tallies);
+                });
+                tallies
+            },
+            |tallies| {
+                Tally::merge_all(&mut total, &tallies);
+                true
+            },
+        );
+        total
+    }
+
+    fn deal_seats(&self, board: CardSet, used: CardSet, ranks: &mut Vec<HandRank>, tallies: &mut [Tally]) {
+        let seat = ranks.len();
+        if seat == self.holes.len() {
+            Tally::record_all(tallies, ranks);
+            return;
+        }
+        let hole = self.holes[seat];
+        let free = (!used).to_vec();
+        for_each_subset(&free, 2 - hole.len(), CardSet::EMPTY, &mut |drawn| {
+            ranks.push(evaluate(board | hole | drawn));
+            self.deal_seats(board, used | drawn, ranks, tallies);
+            ranks.pop();
+        });
+    }
+
+    // draws random runouts in chunks of CHECK_INTERVAL. chunk i always uses the i-th rng
+    // stream of the seed and chunks are tallied in order, so the result does not depend on
+    // the number of threads, unless a time limit cuts the simulation.
+    pub fn simulate(&self, config: &MonteCarloConfig, seed: u64, threads: usize) -> Vec<Tally> {
+        let start = Instant::now();
+        let units = config.max_iterations.div_ceil(CHECK_INTERVAL);
+        let mut total = vec![Tally::default(); self.holes.len()];
+        run_ordered(
+            threads,
+            units,
+            |unit| {
+                let mut rng = stream_rng(seed, unit);
+                let iterations = CHECK_INTERVAL.min(config.max_iterations - unit * CHECK_INTERVAL);
+                self.simulate_chunk(&mut rng, iterations)
+            },
+            |tallies| {
+                Tally::merge_all(&mut total, &tallies);
+                !config.is_done(&total, start)
+            },
+        );
+        total
+    }
+
+    fn simulate_chunk(&self, rng: &mut StdRng, iterations: usize) -> Vec<Tally> {
+        let mut tallies = vec![Tally::default(); self.holes.len()];
+        let mut deck = (!self.known()).to_vec();
+        let missing = 5 - self.board.len();
+        let needed = missing + self.holes.iter().map(|hole| 2 - hole.len()).sum::<usize>();
+        let mut ranks: Vec<HandRank> = Vec::with_capacity(self.holes.len());
+        for _ in 0..iterations {
+            // a partial fisher-yates shuffle puts a uniform sample of the deck in front.
+            for i in 0..needed {
+                let j = rng.gen_range(i..deck.len());
+                deck.swap(i, j);
+            }
+            let mut drawn = deck.iter();
+            let board = self.board | drawn.by_ref().take(missing).collect::<CardSet>();
+            ranks.clear();
+            for &hole in &self.holes {
+                let hole = hole | drawn.by_ref().take(2 - hole.len()).collect::<CardSet>();
+                ranks.push(evaluate(board | hole));
+            }
+            Tally::record_all(&mut tallies, &ranks);
+        }
+        tallies
+    }
+}
+
+// equity of one of hero's combos, and how often hero holds it against the other ranges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComboEquity {
+    pub combo: Combo,
+    pub frequency: f64,
+    pub win: f64,
+    pub tie: f64,
+    pub equity: f64,
+}
+
+// equity of hero's whole range, with the breakdown per combo in the range's order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEquity {
+    pub win: f64,
+    pub tie: f64,
+    pub equity: f64,
+    pub std_error: f64,
+    pub samples: u64,
+    pub exact: bool,
+    pub combos: Vec<ComboEquity>,
+}
+
+// showdowns of one hero combo, each counted with the weight of the combos dealt.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct WeightedTally {
+    pub showdowns: u64,
+    pub weight: f64,
+    pub win: f64,
+    pub tie: f64,
+    pub pot: f64,
+}
+
+impl WeightedTally {
+    fn record(&mut self, weight: f64, ranks: &[HandRank]) {
+        let best = ranks.iter().copied().max();
+        let sharing = ranks.iter().filter(|&&rank| Some(rank) == best).count();
+        self.showdowns += 1;
+        self.weight += weight;
+        if Some(ranks[0]) == best {
+            if sharing == 1 { self.win += weight } else { self.tie += weight }
+            self.pot += weight / sharing as f64;
+        }
+    }
+
+    fn merge(&mut self, other: &WeightedTally) {
+        self.showdowns += other.showdowns;
+        self.weight += other.weight;
+        self.win += other.win;
+        self.tie += other.tie;
+        self.pot += other.pot;
+    }
+}
+
+// a spot where every seat holds a weighted list of combos, hero being seat 0.
+// known hole cards are a list of one combo.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RangeSpot {
+    pub board: CardSet,
+    pub dead: CardSet,
+    pub seats: Vec<Vec<(Combo, f64)>>,
+}
+
+impl RangeSpot {
+    // an upper bound : combos of different seats sharing a card are counted as well.
+    pub fn exact_showdowns(&self) -> u128 {
+        let left = 52 - (self.board | self.dead).len() - 2 * self.seats.len();
+        self.seats.iter().fold(combinations(left, 5 - self.board.len()), |cnt, seat| cnt * seat.len() as u128)
+    }
+
+    // whether the ranges can be dealt at once without sharing a card.
+    pub fn has_deal(&self) -> bool {
+        fn deal(seats: &[Vec<(Combo, f64)>], used: CardSet) -> bool {
+            match seats.split_first() {
+                None => true,
+                Some((seat, rest)) => seat
+                    .iter()
+                    .any(|(combo, _)| combo.card_set().is_disjoint(used) && deal(rest, used | combo.card_set())),
+            }
+        }
+        deal(&self.seats, self.board | self.dead)
+    }
+
+    // walks every deal of the other seats' combos and every runout, one hero combo per unit.
+    pub fn enumerate(&self, threads: usize) -> RangeEquity {
+        let mut tallies = Vec::with_capacity(self.seats[0].len());
+        run_ordered(
+            threads,
+            self.seats[0].len(),
+            |unit| {
+                let (combo, weight) = self.seats[0][unit];
+                let mut tally = WeightedTally::default();
+                let mut hands = vec![combo.card_set()];
+                let used = self.board | self.dead | combo.card_set();
+                self.deal_ranges(1, used, weight, &mut hands, &mut tally);
+                tally
+            },
+            |tally| {
+                tallies.push(tally);
+                true
+            },
+        );
+        self.to_range_equity(&tallies, None)
+    }
+
+    fn deal_ranges(&self, seat: usize, used: CardSet, weight: f64, hands: &mut Vec<CardSet>, tally: &mut WeightedTally) {
+        if seat == self.seats.len() {
+            let remaining = (!used).to_vec();
+            let mut ranks: Vec<HandRank> = Vec::with_capacity(hands.len());
+            for_each_subset(&remaining, 5 - self.board.len(), self.board, &mut |board| {
+                ranks.clear();
+                ranks.extend(hands.iter().map(|&hand| evaluate(board | hand)));
+                tally.record(weight, &ranks);
+            });
+            return;
+        }
+        for &(combo, combo_weight) in &self.seats[seat] {
+            if combo.card_set().is_disjoint(used) {
+                hands.push(combo.card_set());
+                self.deal_ranges(seat + 1, used | combo.card_set(), weight * combo_weight, hands, tally);
+                hands.pop();
+            }
+        }
+    }
+
+    // deals every seat a combo drawn by weight, starting over when two of them share a
+    // card, so each deal comes up as often as the product of its weights. chunks and their
+    // rng streams follow Spot::simulate.
+    pub fn simulate(&self, config: &MonteCarloConfig, seed: u64, threads: usize) -> RangeEquity {
+        let start = Instant::now();
+        let units = config.max_iterations.div_ceil(CHECK_INTERVAL);
+        let mut tallies = vec![WeightedTally::default(); self.seats[0].len()];
+        let mut total = [Tally::default()];
+        run_ordered(
+            threads,
+            units,
+            |unit| {
+                let mut rng = stream_rng(seed, unit);
+                let iterations = CHECK_INTERVAL.min(config.max_iterations - unit * CHECK_INTERVAL);
+                self.simulate_chunk(&mut rng, iterations)
+            },
+            |(chunk, chunk_total)| {
+                tallies.iter_mut().zip(&chunk).for_each(|(tally, other)| tally.merge(other));
+                total[0].merge(&chunk_total);
+                !config.is_done(&total, start)
+            },
+        );
+        self.to_range_equity(&tallies, Some(total[0]))
+    }
+
+    fn simulate_chunk(&self, rng: &mut StdRng, iterations: usize) -> (Vec<WeightedTally>, Tally) {
+        let mut tallies = vec![WeightedTally::default(); self.seats[0].len()];
+        let mut total = Tally::default();
+        let cumulative: Vec<Vec<f64>> = self
+            .seats
+            .iter()
+            .map(|seat| seat.iter().scan(0.0, |sum, &(_, weight)| { *sum += weight; Some(*sum) }).collect())
+            .collect();
+        let mut deck = (!(self.board | self.dead)).to_vec();
+        let mut picks = vec![0; self.seats.len()];
+        let mut ranks: Vec<HandRank> = Vec::with_capacity(self.seats.len());
+        for _ in 0..iterations {
+            let mut used = self.board | self.dead;
+            let mut seat = 0;
+            while seat < self.seats.len() {
+                let sums = &cumulative[seat];
+                let x = rng.gen_range(0.0..sums[sums.len() - 1]);
+                let pick = sums.partition_point(|&sum| sum <= x).min(sums.len() - 1);
+                let cards = self.seats[seat][pick].0.card_set();
+                if cards.is_disjoint(used) {
+                    picks[seat] = pick;
+                    used |= cards;
+                    seat += 1;
+                } else {
+                    used = self.board | self.dead;
+                    seat = 0;
+                }
+            }
+            // the first cards of a random order of the deck that are still free make the runout.
+            let mut board = self.board;
+            let mut i = 0;
+            while board.len() < 5 {
+                let j = rng.gen_range(i..deck.len());
+                deck.swap(i, j);
+                if !used.contains(deck[i]) {
+                    board.insert(deck[i]);
+                }
+                i += 1;
+            }
+            ranks.clear();
+            ranks.extend(picks.iter().enumerate().map(|(seat, &pick)| evaluate(board | self.seats[seat][pick].0.card_set())));
+            tallies[picks[0]].record(1.0, &ranks);
+            total.record_seat(&ranks, 0);
+        }
+        (tallies, total)
+    }
+
+    fn to_range_equity(&self, tallies: &[WeightedTally], sampled: Option<Tally>) -> RangeEquity {
+        let mut sum = WeightedTally::default();
+        tallies.iter().for_each(|tally| sum.merge(tally));
+        let per_weight = |value: f64, weight: f64| if weight > 0.0 { value / weight } else { 0.0 };
+        let combos = self.seats[0]
+            .iter()
+            .zip(tallies)
+            .map(|(&(combo, _), tally)| ComboEquity {
+                combo,
+                frequency: per_weight(tally.weight, sum.weight),
+                win: per_weight(tally.win, tally.weight),
+                tie: per_weight(tally.tie, tally.weight),
+                equity: per_weight(tally.pot, tally.weight),
+            })
+            .collect();
+        RangeEquity {
+            win: per_weight(sum.win, sum.weight),
+            tie: per_weight(sum.tie, sum.weight),
+            equity: per_weight(sum.pot, sum.weight),
+            std_error: sampled.map_or(0.0, |tally| tally.std_error()),
+            samples: sum.showdowns,
+            exact: sampled.is_none(),
+            combos,
+        }
+    }
+}
+
+// the i-th rng stream of a seed : the seed mixed with i times the golden ratio, so nearby
+// streams do not start from nearby states.
+pub(crate) fn stream_rng(seed: u64, stream: usize) -> StdRng {
+    StdRng::seed_from_u64(seed ^ (stream as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+// runs work(unit) for the units 0..units on the given number of threads and hands the
+// results to collect in the order of their units, until collect returns false.
+fn run_ordered<T: Send>(
+    threads: usize,
+    units: usize,
+    work: impl Fn(usize) -> T + Sync,
+    mut collect: impl FnMut(T) -> bool,
+) {
+    if threads <= 1 {
+        for unit in 0..units {
+            if !collect(work(unit)) {
+                return;
+            }
+        }
+        return;
+    }
+    let next_unit = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel::<(usize, T)>();
+    thread::scope(|scope| {
+        for _ in 0..threads.min(units) {
+            let sender = sender.clone();
+            let (next_unit, stopped, work) = (&next_unit, &stopped, &work);
+            scope.spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    let unit = next_unit.fetch_add(1, Ordering::Relaxed);
+                    if unit >= units || sender.send((unit, work(unit))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        let mut pending: BTreeMap<usize, T> = BTreeMap::new();
+        let mut expected = 0;
+        for (unit, result) in receiver.iter() {
+            pending.insert(unit, result);
+            while let Some(result) = pending.remove(&expected) {
+                expected += 1;
+                if !collect(result) {
+                    stopped.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+// calls visit with every subset of `size` cards out of `cards`.
+fn for_each_subset(cards: &[Card], size: usize, acc: CardSet, visit: &mut dyn FnMut(CardSet)) {
+    if size == 0 {
+        return visit(acc);
+    }
+    if cards.len() < size {
+        return;
+    }
+    for i in 0..=cards.len() - size {
+        for_each_subset(&cards[i + 1..], size - 1, acc | CardSet::single(cards[i]), visit);
+    }
+}
+
+#[cfg(test)]
+mod test_for_equity {
+    use crate::cardset::*;
+    use crate::deck::*;
+    use crate::equity::*;
+    use crate::evaluator::*;
+
+    fn set_of(cards: &str) -> CardSet {
+        parse_cards(cards).unwrap().iter().collect()
+    }
+
+    #[test]
+    fn tally_splits_pots() {
+        let low = HandRank::WORST;
+        let high = HandRank::BEST;
+        let mut tallies = [Tally::default(); 3];
+        Tally::record_all(&mut tallies, &[high, low, low]);
+        Tally::record_all(&mut tallies, &[high, high, low]);
+        Tally::record_all(&mut tallies, &[high, high, high]);
+        Tally::record_all(&mut tallies, &[low, high, low]);
+        let tally = tallies[0];
+        assert_eq!((tally.wins, tally.ties, tally.losses), (1, 2, 1));
+        assert_eq!((tallies[1].wins, tallies[1].ties, tallies[1].losses), (1, 2, 1));
+        assert_eq!((tallies[2].wins, tallies[2].ties, tallies[2].losses), (0, 1, 3));
+        // every pot is handed out in full.
+        assert_eq!(tallies.iter().map(|tally| tally.pot_units).sum::<u64>(), 4 * POT_UNITS);
+        let equity = tally.to_equity(true);
+        assert!((equity.equity - (1.0 + 1.0 / 2.0 + 1.0 / 3.0) / 4.0).abs() < 1e-12);
+        assert_eq!(equity.samples, 4);
+        assert_eq!(equity.std_error, 0.0);
+        assert!(tally.to_equity(false).std_error > 0.0);
+    }
+
+    #[test]
+    fn std_error_of_a_coin_flip() {
+        let mut tallies = [Tally::default(); 2];
+        for i in 0..10000 {
+            let (rank, other) = if i % 2 == 0 { (HandRank::BEST, HandRank::WORST) } else { (HandRank::WORST, HandRank::BEST) };
+            Tally::record_all(&mut tallies, &[rank, other]);
+        }
+        let equity = tallies[0].to_equity(false);
+        assert!((equity.equity - 0.5).abs() < 1e-12);
+        assert!((equity.std_error - 0.5 / 9999f64.sqrt()).abs() < 1e-9);
+        let (low, high) = equity.confidence_interval(1.96);
+        assert!((high - low - 2.0 * 1.96 * equity.std_error).abs() < 1e-12);
+    }
+
+    #[test]
+    fn montecarlo_config_stopping_rules() {
+        let start = Instant::now();
+        let mut tallies = [Tally::default(); 2];
+        (0..CHECK_INTERVAL).for_each(|_| Tally::record_all(&mut tallies, &[HandRank::BEST, HandRank::WORST]));
+        assert!(MonteCarloConfig::new(CHECK_INTERVAL).is_done(&tallies, start));
+        assert!(!MonteCarloConfig::new(CHECK_INTERVAL + 1).is_done(&tallies, start));
+        // a sure win has no variance left.
+        let config = MonteCarloConfig::new(usize::MAX).with_target_std_error(0.01);
+        assert!(config.is_done(&tallies, start));
+        let config = MonteCarloConfig::new(usize::MAX).with_time_limit(Duration::ZERO);
+        assert!(config.is_done(&tallies, start));
+    }
+
+    fn spot(board: &str, dead: &str, holes: &[&str]) -> Spot {
+        let set_of = |cards: &str| if cards.is_empty() { CardSet::EMPTY } else { set_of(cards) };
+        Spot {
+            board: set_of(board),
+            dead: set_of(dead),
+            holes: holes.iter().map(|hole| set_of(hole)).collect(),
+        }
+    }
+
+    #[test]
+    fn exact_enumeration_counts_every_showdown() {
+        let turn = spot("Ah Kh 7c 2d", "", &["As Ad", ""]);
+        assert_eq!(turn.exact_showdowns(), 46 * 990);
+        assert_eq!(turn.enumerate(1)[0].showdowns(), 46 * 990);
+        // dead cards leave the enumeration.
+        assert_eq!(spot("Ah Kh 7c 2d", "Qs", &["As Ad", ""]).exact_showdowns(), 45 * 946);
+        // known hands on the river have a single showdown.
+        let river = spot("Ah Kh 7c 2d 3s", "", &["As Ad", "Kd Ks"]);
+        let tallies = river.enumerate(1);
+        assert_eq!((tallies[0].wins, tallies[0].ties, tallies[0].losses), (1, 0, 0));
+        assert_eq!((tallies[1].wins, tallies[1].ties, tallies[1].losses), (0, 0, 1));
+    }
+
+    #[test]
+    fn results_do_not_depend_on_thread_count() {
+        let flop = spot("Ah Kh 7c", "", &["Qh Jh", "As Kd"]);
+        assert_eq!(flop.enumerate(1), flop.enumerate(3));
+        let preflop = spot("", "", &["Qh Jh", "", ""]);
+        let config = MonteCarloConfig::new(5500);
+        let single = preflop.simulate(&config, 42, 1);
+        assert_eq!(single[2].showdowns(), 5500);
+        assert_eq!(single, preflop.simulate(&config, 42, 4));
+        assert_ne!(single, preflop.simulate(&config, 43, 4));
+        let config = MonteCarloConfig::new(1_000_000).with_target_std_error(0.02);
+        assert_eq!(preflop.simulate(&config, 42, 1), preflop.simulate(&config, 42, 3));
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use crate::cardset::CardSet;
+use crate::deck::{Card, Rank, Suit};
+
+// equivalence class of a hand : 1 is the weakest 7-5-4-3-2 high card, 7462 a royal flush.
+// any two hands with the same rank are tied at showdown.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct HandRank(u16);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    Top,
+    OnePair,
+    TwoPairs,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl HandCategory {
+    pub const ALL: [HandCategory; 9] = [
+        HandCategory::Top,
+        HandCategory::OnePair,
+        HandCategory::TwoPairs,
+        HandCategory::ThreeOfAKind,
+        HandCategory::Straight,
+        HandCategory::Flush,
+        HandCategory::FullHouse,
+        HandCategory::FourOfAKind,
+        HandCategory::StraightFlush,
+    ];
+}
+
+impl fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::StraightFlush => "Straight Flush",
+            Self::FourOfAKind => "Four Of A Kind",
+            Self::FullHouse => "Full House",
+            Self::Flush => "Flush",
+            Self::Straight => "Straight",
+            Self::ThreeOfAKind => "Three Of A Kind",
+            Self::TwoPairs => "Two Pairs",
+            Self::OnePair => "One Pair",
+            Self::Top => "Top",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl HandRank {
+    pub const WORST: HandRank = HandRank(1);
+    pub const BEST: HandRank = HandRank(CLASS_CNT);
+
+    pub fn new(value: u16) -> Option<Self> {
+        if (1..=CLASS_CNT).contains(&value) {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    pub fn value(self) -> u16 {
+        self.0
+    }
+
+    pub fn category(self) -> HandCategory {
+        tables().classes[self.0 as usize].0
+    }
+
+    // ranks of the made five cards, in the order Grade lists them.
+    pub fn ranks(self) -> [Rank; 5] {
+        tables().classes[self.0 as usize].1
+    }
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranks: String = self.ranks().iter().map(|rank| rank.to_char()).collect();
+        write!(f, "{} ({})", self.category(), ranks)
+    }
+}
+
+// evaluates 5, 6 or 7 cards into the rank of their best five.
+pub fn evaluate(cards: CardSet) -> HandRank {
+    let cnt = cards.len();
+    assert!((5..=7).contains(&cnt), "only 5 to 7 cards can be evaluated.");
+    let t = tables();
+    // with at most 7 cards a flush always beats whatever the other cards make.
+    for suit in Suit::ALL {
+        let mask = cards.suit_mask(suit);
+        if mask.count_ones() >= 5 {
+            return HandRank(t.flush[mask as usize]);
+        }
+    }
+    let masks = Suit::ALL.map(|suit| cards.suit_mask(suit));
+    let mut remain = cnt;
+    let mut index = 0;
+    for rank in 0..13 {
+        let rank_cnt = masks.iter().map(|mask| ((mask >> rank) & 1) as usize).sum::<usize>();
+        index += t.offsets[rank][remain][rank_cnt];
+        remain -= rank_cnt;
+    }
+    HandRank(t.unsuited[cnt - 5][index as usize])
+}
+
+pub fn evaluate_cards(cards: &[Card]) -> HandRank {
+    evaluate(cards.iter().collect())
+}
+
+const CLASS_CNT: u16 = 7462;
+
+struct Tables {
+    // indexed by rank value; index 0 is unused.
+    classes: Vec<(HandCategory, [Rank; 5])>,
+    // best flush or straight flush for a 13-bit rank mask of one suit.
+    flush: Vec<u16>,
+    // best hand of 5, 6 and 7 cards without a flush, indexed by their rank counts.
+    unsuited: [Vec<u16>; 3],
+    // offsets[rank][cards left][count of this rank] ranks a vector of rank counts.
+    offsets: [[[u32; 5]; 8]; 13],
+}
+
+type Key = (HandCategory, [usize; 5]);
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(build_tables)
+}
+
+fn build_tables() -> Tables {
+    // every distinct five card hand, weakest first.
+    let mut keys: Vec<Key> = Vec::new();
+    for_each_counts(5, &mut |counts| {
+        keys.push(key_of(counts, false));
+        if counts.iter().all(|&cnt| cnt <= 1) {
+            keys.push(key_of(counts, true));
+        }
+    });
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys.len(), CLASS_CNT as usize);
+
+    let mut class_of: HashMap<Key, u16> = HashMap::new();
+    let mut classes = vec![(HandCategory::Top, [Rank::Two; 5])];
+    for (i, key) in keys.iter().enumerate() {
+        class_of.insert(*key, i as u16 + 1);
+        classes.push((key.0, key.1.map(|rank| Rank::ALL[rank])));
+    }
+
+    let mut flush = vec![0; 1 << 13];
+    for (mask, class) in flush.iter_mut().enumerate() {
+        // a suit never holds more than the 7 evaluated cards.
+        if !(5..=7).contains(&mask.count_ones()) {
+            continue;
+        }
+        let counts: [usize; 13] = std::array::from_fn(|rank| (mask >> rank) & 1);
+        *class = best_of(&counts, true, &class_of);
+    }
+
+    let mut ways = [[0u32; 8]; 14];
+    ways[13][0] = 1;
+    for rank in (0..13).rev() {
+        for left in 0..8 {
+            ways[rank][left] = (0..=usize::min(4, left)).map(|cnt| ways[rank + 1][left - cnt]).sum();
+        }
+    }
+    let mut offsets = [[[0u32; 5]; 8]; 13];
+    for rank in 0..13 {
+        for left in 0..8 {
+            for cnt in 1..5 {
+                let skipped = if cnt - 1 <= left { ways[rank + 1][left - (cnt - 1)] } else { 0 };
+                offsets[rank][left][cnt] = offsets[rank][left][cnt - 1] + skipped;
+            }
+        }
+    }
+
+    let unsuited: [Vec<u16>; 3] = std::array::from_fn(|i| {
+        let cnt = i + 5;
+        let mut table = vec![0; ways[0][cnt] as usize];
+        for_each_counts(cnt, &mut |counts| {
+            let mut left = cnt;
+            let mut index = 0;
+            for rank in 0..13 {
+                index += offsets[rank][left][counts[rank]];
+                left -= counts[rank];
+            }
+            table[index as usize] = best_of(counts, false, &class_of);
+        });
+        table
+    });
+
+    Tables { classes, flush, unsuited, offsets }
+}
+
+// calls f with every vector of rank counts (at most 4 of a rank) adding up to cnt.
+fn for_each_counts(cnt: usize, f: &mut dyn FnMut(&[usize; 13])) {
+    fn fill(rank: usize, left: usize, counts: &mut [usize; 13], f: &mut dyn FnMut(&[usize; 13])) {
+        if rank == 13 {
+            if left == 0 {
+                f(counts);
+            }
+            return;
+        }
+        for cnt in 0..=usize::min(4, left) {
+            counts[rank] = cnt;
+            fill(rank + 1, left - cnt, counts, f);
+        }
+        counts[rank] = 0;
+    }
+    fill(0, cnt, &mut [0; 13], f);
+}
+
+// the class of the best five cards chosen from the given rank counts.
+fn best_of(counts: &[usize; 13], suited: bool, class_of: &HashMap<Key, u16>) -> u16 {
+    let total: usize = counts.iter().sum();
+    let mut best = 0;
+    let mut pick = [0usize; 13];
+    fn choose(
+        rank: usize,
+        left: usize,
+        counts: &[usize; 13],
+        pick: &mut [usize; 13],
+        visit: &mut dyn FnMut(&[usize; 13]),
+    ) {
+        if left == 0 {
+            visit(pick);
+            return;
+        }
+        if rank == 13 {
+            return;
+        }
+        for cnt in 0..=usize::min(counts[rank], left) {
+            pick[rank] = cnt;
+            choose(rank + 1, left - cnt, counts, pick, visit);
+        }
+        pick[rank] = 0;
+    }
+    debug_assert!(total >= 5);
+    choose(0, 5, counts, &mut pick, &mut |five| {
+        best = u16::max(best, class_of[&key_of(five, suited)]);
+    });
+    best
+}
+
+// category and tie-breaking ranks of exactly five cards given as rank counts.
+fn key_of(counts: &[usize; 13], suited: bool) -> Key {
+    // ranks ordered by how many times they appear, then by rank, highest first.
+    let mut ranks = [0usize; 5];
+    let mut i = 0;
+    for cnt in (1..=4).rev() {
+        for rank in (0..13).rev() {
+            if counts[rank] == cnt {
+                ranks[i..i + cnt].fill(rank);
+                i += cnt;
+            }
+        }
+    }
+    let distinct = counts.iter().filter(|&&cnt| cnt > 0).count();
+    let straight = distinct == 5 && (ranks[0] - ranks[4] == 4 || ranks == [12, 3, 2, 1, 0]);
+    if straight && ranks[0] == 12 && ranks[1] == 3 {
+        ranks = [3, 2, 1, 0, 12];
+    }
+    let category = match (suited, straight, counts.iter().max()) {
+        (true, true, _) => HandCategory::StraightFlush,
+        (true, false, _) => HandCategory::Flush,
+        (false, true, _) => HandCategory::Straight,
+        (false, false, Some(4)) => HandCategory::FourOfAKind,
+        (false, false, Some(3)) if distinct == 2 => HandCategory::FullHouse,
+        (false, false, Some(3)) => HandCategory::ThreeOfAKind,
+        (false, false, Some(2)) if distinct == 3 => HandCategory::TwoPairs,
+        (false, false, Some(2)) => HandCategory::OnePair,
+        _ => HandCategory::Top,
+    };
+    (category, ranks)
+}
+
+#[cfg(test)]
+mod test_for_evaluator {
+    use crate::cardset::*;
+    use crate::deck::*;
+    use crate::evaluator::*;
+
+    fn rank_of(cards: &str) -> HandRank {
+        evaluate_cards(&parse_cards(cards).unwrap())
+    }
+
+    #[test]
+    fn class_counts_per_category() {
+        let mut cnts: HashMap<HandCategory, usize> = HashMap::new();
+        for value in 1..=7462 {
+            *cnts.entry(HandRank::new(value).unwrap().category()).or_default() += 1;
+        }
+        let expected = [1277, 2860, 858, 858, 10, 1277, 156, 156, 10];
+        for (category, cnt) in HandCategory::ALL.iter().zip(expected) {
+            assert_eq!(cnts[category], cnt, "{}", category);
+        }
+        assert_eq!(HandRank::new(0), None);
+        assert_eq!(HandRank::new(7463), None);
+    }
+
+    #[test]
+    fn extreme_hands() {
+        assert_eq!(rank_of("As Ks Qs Js Ts"), HandRank::BEST);
+        assert_eq!(rank_of("7s 5d 4h 3c 2s"), HandRank::WORST);
+        assert_eq!(rank_of("As Ks Qs Js Ts 9s 8s"), HandRank::BEST);
+        assert_eq!(rank_of("5s 4d 3h 2c As").category(), HandCategory::Straight);
+        assert_eq!(rank_of("5s 4d 3h 2c As").ranks(), [Rank::Five, Rank::Four, Rank::Three, Rank::Two, Rank::Ace]);
+    }
+
+    #[test]
+    fn best_five_of_seven() {
+        assert_eq!(rank_of("Ah Kh 7h 2h 9c Qh Qd").category(), HandCategory::Flush);
+        assert_eq!(rank_of("Ah Ad Ac Kh Kd Kc 2s"), rank_of("Ah Ad Ac Kh Kd"));
+        assert_eq!(rank_of("9h 9d 5c 5s 3h 3d Kc"), rank_of("9h 9d 5c 5s Kc"));
+        assert_eq!(rank_of("9h 9d 5c 5s 3h 3d 2c"), rank_of("9h 9d 5c 5s 3h"));
+        assert_eq!(rank_of("6h 5d 4c 3s 2h Ad 9c"), rank_of("6h 5d 4c 3s 2h"));
+        assert_eq!(rank_of("Th Td Tc Ts 2h 2d 2c").category(), HandCategory::FourOfAKind);
+        assert!(rank_of("As Ad 8c 7s 3h") > rank_of("Ks Kd Qc Js 9h"));
+        assert!(rank_of("2s 3d 4c 5s 6h") > rank_of("As 5d 4c 3s 2h"));
+        assert_eq!(rank_of("As Kd Qh Js 9c 2d 3h"), rank_of("Ac Kc Qc Jd 9s 4c 3d"));
+    }
+
+    #[test]
+    fn evaluation_ignores_suits_without_flush() {
+        let set: CardSet = parse_cards("Ks Kd 8c 7s 3h 2d").unwrap().iter().collect();
+        let swapped: CardSet = parse_cards("Kh Kc 8d 7h 3s 2c").unwrap().iter().collect();
+        assert_eq!(evaluate(set), evaluate(swapped));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::deck::Card;
+use crate::cardset::CardSet;
+use crate::deck::{Card, Rank, Suit};
+use crate::evaluator::{evaluate_cards, HandCategory, HandRank};
 use std::cmp::Ordering;
 use std::fmt;
 
@@ -25,59 +27,41 @@ impl PartialEq for Grade {
     fn eq(&self, other: &Self) -> bool {
         self.as_value().eq(&other.as_value())
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.as_value().ne(&other.as_value())
-    }
 }
 
 impl fmt::Display for Grade {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
-    }
-}
-
-impl FromIterator<Card> for [Card; 5] {
-    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
-        let mut arr = [Card { shape: 5, num: 13 }; 5];
-        iter.into_iter()
-            .enumerate()
-            .for_each(|(i, card)| arr[i] = card);
-        arr
+        match self {
+            Self::StraightFlush(cards) => write!(f, "Straight Flush	: {:?}", cards),
+            Self::FourOfAKind(cards) => write!(f, "Four Of A Kind	: {:?}", cards),
+            Self::FullHouse(cards) => write!(f, "Full House		: {:?}", cards),
+            Self::Flush(cards) => write!(f, "Flush		: {:?}", cards),
+            Self::Straight(cards) => write!(f, "Straight		: {:?}", cards),
+            Self::ThreeOfAKind(cards) => write!(f, "Three Of A Kind	: {:?}", cards),
+            Self::TwoPairs(cards) => write!(f, "TwoPairs		: {:?}", cards),
+            Self::OnePair(cards) => write!(f, "One Pair		: {:?}", cards),
+            Self::Top(cards) => write!(f, "Top			: {:?}", cards),
+        }
     }
 }
 
 impl Grade {
-    pub fn to_string(&self) -> String {
+    fn as_value(&self) -> (u8, Rank, Rank, Rank, Rank, Rank) {
         match self {
-            Self::StraightFlush(cards) => format!("Straight Flush	: {:?}", cards),
-            Self::FourOfAKind(cards) => format!("Four Of A Kind	: {:?}", cards),
-            Self::FullHouse(cards) => format!("Full House		: {:?}", cards),
-            Self::Flush(cards) => format!("Flush		: {:?}", cards),
-            Self::Straight(cards) => format!("Straight		: {:?}", cards),
-            Self::ThreeOfAKind(cards) => format!("Three Of A Kind	: {:?}", cards),
-            Self::TwoPairs(cards) => format!("TwoPairs		: {:?}", cards),
-            Self::OnePair(cards) => format!("One Pair		: {:?}", cards),
-            Self::Top(cards) => format!("Top			: {:?}", cards),
-        }
-    } // end :: to_string()
-
-    fn as_value(&self) -> (u8, usize, usize, usize, usize, usize) {
-        match self {
-            Self::StraightFlush([a, b, c, d, e]) => (8, a.num, b.num, c.num, d.num, e.num),
-            Self::FourOfAKind([a, b, c, d, e]) => (7, a.num, b.num, c.num, d.num, e.num),
-            Self::FullHouse([a, b, c, d, e]) => (6, a.num, b.num, c.num, d.num, e.num),
-            Self::Flush([a, b, c, d, e]) => (5, a.num, b.num, c.num, d.num, e.num),
-            Self::Straight([a, b, c, d, e]) => (4, a.num, b.num, c.num, d.num, e.num),
-            Self::ThreeOfAKind([a, b, c, d, e]) => (3, a.num, b.num, c.num, d.num, e.num),
-            Self::TwoPairs([a, b, c, d, e]) => (2, a.num, b.num, c.num, d.num, e.num),
-            Self::OnePair([a, b, c, d, e]) => (1, a.num, b.num, c.num, d.num, e.num),
-            Self::Top([a, b, c, d, e]) => (0, a.num, b.num, c.num, d.num, e.num),
+            Self::StraightFlush([a, b, c, d, e]) => (8, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::FourOfAKind([a, b, c, d, e]) => (7, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::FullHouse([a, b, c, d, e]) => (6, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::Flush([a, b, c, d, e]) => (5, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::Straight([a, b, c, d, e]) => (4, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::ThreeOfAKind([a, b, c, d, e]) => (3, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::TwoPairs([a, b, c, d, e]) => (2, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::OnePair([a, b, c, d, e]) => (1, a.rank, b.rank, c.rank, d.rank, e.rank),
+            Self::Top([a, b, c, d, e]) => (0, a.rank, b.rank, c.rank, d.rank, e.rank),
         }
     } // end :: as_value()
 
     pub fn new(cards: &[Card]) -> Self {
-        let mut cards: Vec<Card> = cards.clone().to_vec();
+        let mut cards: Vec<Card> = cards.to_vec();
         if let Some(sf) = Self::is_straight_flush(&mut cards) {
             return sf;
         }
@@ -105,6 +89,59 @@ impl Grade {
         Self::Top(Self::extract_top5(&mut cards))
     } // end :: new()
 
+    pub fn from_set(cards: CardSet) -> Self {
+        Self::new(&cards.to_vec())
+    }
+
+    // rebuilds the made hand of the cards from their evaluated rank, which must be evaluate(cards).
+    pub fn from_rank(rank: HandRank, cards: CardSet) -> Self {
+        let flush_shape: Option<Suit> = Suit::ALL
+            .into_iter()
+            .find(|&suit| cards.suit_mask(suit).count_ones() >= 5);
+        let category = rank.category();
+        let mut left = cards;
+        let made_cards: [Card; 5] = rank.ranks().map(|num| {
+            let card = match (category, flush_shape) {
+                (HandCategory::StraightFlush | HandCategory::Flush, Some(suit)) => Card::new(num, suit),
+                _ => left
+                    .iter()
+                    .find(|card| card.rank == num)
+                    .expect("the rank does not belong to the cards."),
+            };
+            left.remove(card);
+            card
+        });
+        match category {
+            HandCategory::StraightFlush => Self::StraightFlush(made_cards),
+            HandCategory::FourOfAKind => Self::FourOfAKind(made_cards),
+            HandCategory::FullHouse => Self::FullHouse(made_cards),
+            HandCategory::Flush => Self::Flush(made_cards),
+            HandCategory::Straight => Self::Straight(made_cards),
+            HandCategory::ThreeOfAKind => Self::ThreeOfAKind(made_cards),
+            HandCategory::TwoPairs => Self::TwoPairs(made_cards),
+            HandCategory::OnePair => Self::OnePair(made_cards),
+            HandCategory::Top => Self::Top(made_cards),
+        }
+    }
+
+    pub fn cards(&self) -> [Card; 5] {
+        match self {
+            Self::StraightFlush(cards)
+            | Self::FourOfAKind(cards)
+            | Self::FullHouse(cards)
+            | Self::Flush(cards)
+            | Self::Straight(cards)
+            | Self::ThreeOfAKind(cards)
+            | Self::TwoPairs(cards)
+            | Self::OnePair(cards)
+            | Self::Top(cards) => *cards,
+        }
+    }
+
+    pub fn rank(&self) -> HandRank {
+        evaluate_cards(&self.cards())
+    }
+
     fn extract_top5(cards: &mut [Card]) -> [Card; 5] {
         cards.sort();
         Self::take_highest_5_cards(cards)
@@ -112,59 +149,58 @@ impl Grade {
 
     fn shape_counts(cards: &[Card]) -> [usize; 4] {
         let mut cnts: [usize; 4] = [0; 4];
-        cards.iter().for_each(|&card| cnts[card.shape] += 1);
-        return cnts;
+        cards.iter().for_each(|&card| cnts[card.suit.index()] += 1);
+        cnts
     }
 
     fn get_major_shape(cards: &[Card], threshold: usize) -> Option<usize> {
-        let shape_cnts = Self::shape_counts(&cards);
-        return shape_cnts
+        let shape_cnts = Self::shape_counts(cards);
+        shape_cnts
             .iter()
             .enumerate()
             .filter(|&(_shape, shape_cnt)| *shape_cnt >= threshold)
             .map(|(shape, _shape_cnt)| shape)
-            .nth(0);
+            .nth(0)
     }
 
     fn filter_by_shape(cards: &[Card], shape: usize) -> Vec<Card> {
-        return cards
-            .into_iter()
+        cards
+            .iter()
             .cloned()
-            .filter(|&card| card.shape == shape)
-            .collect::<Vec<Card>>();
+            .filter(|&card| card.suit.index() == shape)
+            .collect::<Vec<Card>>()
     }
 
     fn num_counts(cards: &[Card]) -> [usize; 13] {
         let mut cnts: [usize; 13] = [0; 13];
-        cards.iter().for_each(|&card| cnts[card.num] += 1);
-        return cnts;
+        cards.iter().for_each(|&card| cnts[card.rank.index()] += 1);
+        cnts
     }
 
     fn get_major_num(cards: &[Card], num_of_cards: usize) -> Option<usize> {
-        let num_cnts = Self::num_counts(&cards);
-        return num_cnts
+        let num_cnts = Self::num_counts(cards);
+        num_cnts
             .iter()
             .enumerate()
             .filter(|&(_num, num_cnt)| *num_cnt == num_of_cards)
             .map(|(num, _num_cnt)| num)
-            .nth(0);
+            .nth(0)
     }
 
     fn take_highest_5_cards(cards: &[Card]) -> [Card; 5] {
-        return cards.iter().cloned().rev().take(5).collect::<[Card; 5]>();
+        // cards are sorted ascending, so the made hand is the last five in reverse.
+        std::array::from_fn(|i| cards[cards.len() - 1 - i])
     }
 
     pub fn is_straight_flush(cards: &mut [Card]) -> Option<Self> {
         // search a major shape of the cards.
 
-        let opt_major_shape: Option<_> = Self::get_major_shape(&cards, 5);
-        if opt_major_shape.is_none() {
-            return None;
-        }
+        let opt_major_shape: Option<_> = Self::get_major_shape(cards, 5);
+        opt_major_shape?;
         let major_shape = opt_major_shape.unwrap();
 
         // filter the cards by the major shape.
-        let mut shape_filtered_cards: Vec<_> = Self::filter_by_shape(&cards, major_shape);
+        let mut shape_filtered_cards: Vec<_> = Self::filter_by_shape(cards, major_shape);
         // reuse a code for checking if it is straight.
         match Self::is_straight(&mut shape_filtered_cards) {
             Some(Self::Straight(same_shaped_straight_cards)) => {
@@ -177,20 +213,21 @@ impl Grade {
 
     fn is_four_of_a_kind(cards: &mut [Card]) -> Option<Self> {
         // search a major num of the cards.
-        let four_kind_num: Option<_> = Self::get_major_num(&cards, 4);
+        let four_kind_num: Option<_> = Self::get_major_num(cards, 4);
 
         // check
         match four_kind_num {
             Some(major_num) => {
                 cards.sort_by_key(|&card| {
-                    if card.num == major_num {
-                        13 + card.num
+                    let rank = card.rank.index();
+                    if rank == major_num {
+                        13 + rank
                     } else {
-                        card.num
+                        rank
                     }
                 });
-                let made_cards = Self::take_highest_5_cards(&cards);
-                return Some(Self::FourOfAKind(made_cards));
+                let made_cards = Self::take_highest_5_cards(cards);
+                Some(Self::FourOfAKind(made_cards))
             }
             None => None,
         } // end of match
@@ -199,7 +236,7 @@ impl Grade {
     fn is_full_house(cards: &mut [Card]) -> Option<Self> {
         // search major nums of the cards.
         //let triplets : Option<_> = Self::get_major_num(&cards, 3);
-        let num_cnts = Self::num_counts(&cards);
+        let num_cnts = Self::num_counts(cards);
         // (3, 2, 1, 1) or (3, 2, 2) or (3, 3, 1) => full house
         let triplets = num_cnts
             .iter()
@@ -209,7 +246,7 @@ impl Grade {
             .map(|(num, _num_cnt)| num)
             .take(2)
             .collect::<Vec<usize>>();
-        if triplets.len() == 0 {
+        if triplets.is_empty() {
             return None;
         }
         let twins = num_cnts
@@ -225,16 +262,17 @@ impl Grade {
         }
 
         cards.sort_by_key(|&card| {
-            if triplets.contains(&card.num) {
-                card.num + 26
-            } else if twins.contains(&card.num) {
-                card.num + 13
+            let rank = card.rank.index();
+            if triplets.contains(&rank) {
+                rank + 26
+            } else if twins.contains(&rank) {
+                rank + 13
             } else {
-                card.num
+                rank
             }
         });
-        let made_cards = Self::take_highest_5_cards(&cards);
-        return Some(Self::FullHouse(made_cards));
+        let made_cards = Self::take_highest_5_cards(cards);
+        Some(Self::FullHouse(made_cards))
     }
 
     fn is_flush(cards: &mut [Card]) -> Option<Self> {
@@ -243,13 +281,14 @@ impl Grade {
         match opt_major_shape {
             Some(major_shape) => {
                 cards.sort_by_key(|&card| {
-                    if card.shape == major_shape {
-                        card.num + 13
+                    let rank = card.rank.index();
+                    if card.suit.index() == major_shape {
+                        rank + 13
                     } else {
-                        card.num
+                        rank
                     }
                 });
-                let made_cards = Self::take_highest_5_cards(&cards);
+                let made_cards = Self::take_highest_5_cards(cards);
                 Some(Self::Flush(made_cards))
             }
             None => None,
@@ -260,12 +299,13 @@ impl Grade {
         // search straight nums.
         let num_cnts = Self::num_counts(cards);
         let mut cards = cards.to_vec();
-        cards.dedup_by_key(|card| card.num); // delete duplicates by card.num
+        cards.sort();
+        cards.dedup_by_key(|card| card.rank); // delete duplicates by card.rank
                        // must consider a special case : A2345 (0,1,2,3,12)
         let mut consec_cnt: u8 = 0;
         let mut ace_exist: bool = false;
         for (num, num_cnt) in num_cnts.into_iter().enumerate().rev() {
-            if num == 12 {
+            if num == 12 && num_cnt > 0 {
                 ace_exist = true;
             }
             if num_cnt == 0 {
@@ -275,10 +315,11 @@ impl Grade {
                 match consec_cnt {
                     5 => {
                         cards.sort_by_key(|&card| {
-                            if card.num >= num && card.num <= num + 4 {
-                                card.num + 20
+                            let rank = card.rank.index();
+                            if rank >= num && rank <= num + 4 {
+                                rank + 20
                             } else {
-                                card.num
+                                rank
                             }
                         });
                         let made_cards = Self::take_highest_5_cards(&cards);
@@ -286,12 +327,13 @@ impl Grade {
                     }
                     4 if num == 0 && ace_exist => {
                         cards.sort_by_key(|&card| {
-                            if card.num == 12 {
-                                card.num + 20 - 13
-                            } else if card.num <= 3 {
-                                card.num + 20
+                            let rank = card.rank.index();
+                            if rank == 12 {
+                                rank + 20 - 13
+                            } else if rank <= 3 {
+                                rank + 20
                             } else {
-                                card.num
+                                rank
                             }
                         });
 
@@ -302,27 +344,28 @@ impl Grade {
                 };
             }
         }
-        return None;
+        None
     }
 
     fn is_three_of_a_kind(cards: &mut [Card]) -> Option<Self> {
         // grade : 3
-        let num_triplet: Option<_> = Self::get_major_num(&cards, 3);
+        let num_triplet: Option<_> = Self::get_major_num(cards, 3);
         match num_triplet {
             Some(num) => {
                 cards.sort_by_key(|&card| {
-                    if card.num == num {
-                        card.num + 13
+                    let rank = card.rank.index();
+                    if rank == num {
+                        rank + 13
                     } else {
-                        card.num
+                        rank
                     }
                 });
 
-                let made_cards = Self::take_highest_5_cards(&cards);
-                return Some(Self::ThreeOfAKind(made_cards));
+                let made_cards = Self::take_highest_5_cards(cards);
+                Some(Self::ThreeOfAKind(made_cards))
             }
             None => {
-                return None;
+                None
             }
         }
     }
@@ -330,33 +373,35 @@ impl Grade {
     fn is_two_pairs(cards: &mut [Card]) -> Option<Self> {
         // grade : 2
         // (2, 2, 1, 1, 1), (2, 2, 2, 1)
+        // with three pairs the lowest one only competes for the kicker.
         let num_cnts = Self::num_counts(cards);
         let num_pairs = num_cnts
             .into_iter()
             .enumerate()
             .filter(|&(_num, num_cnt)| num_cnt == 2)
             .rev()
-            .take(3)
+            .take(2)
             .map(|(num, _num_cnt)| num)
             .collect::<Vec<usize>>();
         let num_pairs_len = num_pairs.len();
         match num_pairs_len {
             0..=1 => {
-                return None;
+                None
             }
             2..=3 => {
                 cards.sort_by_key(|&card| {
-                    if num_pairs.contains(&card.num) {
-                        card.num + 13
+                    let rank = card.rank.index();
+                    if num_pairs.contains(&rank) {
+                        rank + 13
                     } else {
-                        card.num
+                        rank
                     }
                 });
-                let made_cards = Self::take_highest_5_cards(&cards);
-                return Some(Self::TwoPairs(made_cards));
+                let made_cards = Self::take_highest_5_cards(cards);
+                Some(Self::TwoPairs(made_cards))
             }
             _ => {
-                return None;
+                None
             }
         }
     }
@@ -372,18 +417,19 @@ impl Grade {
             .nth(0);
         match pair_num {
             None => {
-                return None;
+                None
             }
             Some(num) => {
                 cards.sort_by_key(|&card| {
-                    if card.num == num {
-                        card.num + 13
+                    let rank = card.rank.index();
+                    if rank == num {
+                        rank + 13
                     } else {
-                        card.num
+                        rank
                     }
                 });
-                let made_cards = Self::take_highest_5_cards(&cards);
-                return Some(Self::OnePair(made_cards));
+                let made_cards = Self::take_highest_5_cards(cards);
+                Some(Self::OnePair(made_cards))
             }
         }
     }
@@ -410,11 +456,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 3, num: 11 },
-                    Card { shape: 3, num: 10 },
-                    Card { shape: 3, num: 9 },
-                    Card { shape: 3, num: 8 },
-                    Card { shape: 3, num: 7 }
+                    Card::new(Rank::King, Suit::Club),
+                    Card::new(Rank::Queen, Suit::Club),
+                    Card::new(Rank::Jack, Suit::Club),
+                    Card::new(Rank::Ten, Suit::Club),
+                    Card::new(Rank::Nine, Suit::Club)
                 ])
             );
         }
@@ -441,11 +487,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 0, num: 12 },
-                    Card { shape: 1, num: 12 },
-                    Card { shape: 2, num: 12 },
-                    Card { shape: 3, num: 12 },
-                    Card { shape: 0, num: 2 }
+                    Card::new(Rank::Ace, Suit::Spade),
+                    Card::new(Rank::Ace, Suit::Diamond),
+                    Card::new(Rank::Ace, Suit::Heart),
+                    Card::new(Rank::Ace, Suit::Club),
+                    Card::new(Rank::Four, Suit::Spade)
                 ])
             );
         }
@@ -454,13 +500,13 @@ mod test_for_grade {
     #[test]
     fn test_full_house() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 9 },
-            Card { shape: 1, num: 9 },
-            Card { shape: 2, num: 5 },
-            Card { shape: 3, num: 9 },
-            Card { shape: 2, num: 8 },
-            Card { shape: 1, num: 5 },
-            Card { shape: 0, num: 5 },
+            Card::new(Rank::Jack, Suit::Spade),
+            Card::new(Rank::Jack, Suit::Diamond),
+            Card::new(Rank::Seven, Suit::Heart),
+            Card::new(Rank::Jack, Suit::Club),
+            Card::new(Rank::Ten, Suit::Heart),
+            Card::new(Rank::Seven, Suit::Diamond),
+            Card::new(Rank::Seven, Suit::Spade),
         ];
         let fh = Grade::is_full_house(&mut cards);
         assert!(fh.is_some());
@@ -469,11 +515,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 0, num: 9 },
-                    Card { shape: 1, num: 9 },
-                    Card { shape: 3, num: 9 },
-                    Card { shape: 0, num: 5 },
-                    Card { shape: 1, num: 5 }
+                    Card::new(Rank::Jack, Suit::Spade),
+                    Card::new(Rank::Jack, Suit::Diamond),
+                    Card::new(Rank::Jack, Suit::Club),
+                    Card::new(Rank::Seven, Suit::Spade),
+                    Card::new(Rank::Seven, Suit::Diamond)
                 ])
             );
         }
@@ -482,13 +528,13 @@ mod test_for_grade {
     #[test]
     fn test_flush() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 3, num: 1 },
-            Card { shape: 1, num: 2 },
-            Card { shape: 2, num: 3 },
-            Card { shape: 3, num: 4 },
-            Card { shape: 3, num: 6 },
-            Card { shape: 3, num: 9 },
-            Card { shape: 3, num: 12 },
+            Card::new(Rank::Three, Suit::Club),
+            Card::new(Rank::Four, Suit::Diamond),
+            Card::new(Rank::Five, Suit::Heart),
+            Card::new(Rank::Six, Suit::Club),
+            Card::new(Rank::Eight, Suit::Club),
+            Card::new(Rank::Jack, Suit::Club),
+            Card::new(Rank::Ace, Suit::Club),
         ];
         let fl = Grade::is_flush(&mut cards);
         assert!(fl.is_some());
@@ -497,11 +543,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 3, num: 1 },
-                    Card { shape: 3, num: 4 },
-                    Card { shape: 3, num: 6 },
-                    Card { shape: 3, num: 9 },
-                    Card { shape: 3, num: 12 }
+                    Card::new(Rank::Three, Suit::Club),
+                    Card::new(Rank::Six, Suit::Club),
+                    Card::new(Rank::Eight, Suit::Club),
+                    Card::new(Rank::Jack, Suit::Club),
+                    Card::new(Rank::Ace, Suit::Club)
                 ])
             );
         }
@@ -511,13 +557,13 @@ mod test_for_grade {
     // Straight([HQ, CQ, CJ, ST, S9])
     fn test_straight() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 2 },
-            Card { shape: 1, num: 11 },
-            Card { shape: 2, num: 10 },
-            Card { shape: 3, num: 10 },
-            Card { shape: 3, num: 9 },
-            Card { shape: 0, num: 8 },
-            Card { shape: 3, num: 7 },
+            Card::new(Rank::Four, Suit::Spade),
+            Card::new(Rank::King, Suit::Diamond),
+            Card::new(Rank::Queen, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Club),
+            Card::new(Rank::Jack, Suit::Club),
+            Card::new(Rank::Ten, Suit::Spade),
+            Card::new(Rank::Nine, Suit::Club),
         ];
         let st = Grade::is_straight(&mut cards);
         assert!(st.is_some());
@@ -525,11 +571,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 1, num: 11 },
-                    Card { shape: 2, num: 10 },
-                    Card { shape: 3, num: 9 },
-                    Card { shape: 0, num: 8 },
-                    Card { shape: 3, num: 7 }
+                    Card::new(Rank::King, Suit::Diamond),
+                    Card::new(Rank::Queen, Suit::Heart),
+                    Card::new(Rank::Jack, Suit::Club),
+                    Card::new(Rank::Ten, Suit::Spade),
+                    Card::new(Rank::Nine, Suit::Club)
                 ])
             );
         }
@@ -538,13 +584,13 @@ mod test_for_grade {
     #[test]
     fn test_three_of_a_kind() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 3 },
-            Card { shape: 0, num: 11 },
-            Card { shape: 1, num: 2 },
-            Card { shape: 2, num: 12 },
-            Card { shape: 3, num: 3 },
-            Card { shape: 2, num: 3 },
-            Card { shape: 0, num: 10 },
+            Card::new(Rank::Five, Suit::Spade),
+            Card::new(Rank::King, Suit::Spade),
+            Card::new(Rank::Four, Suit::Diamond),
+            Card::new(Rank::Ace, Suit::Heart),
+            Card::new(Rank::Five, Suit::Club),
+            Card::new(Rank::Five, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Spade),
         ];
         let tk = Grade::is_three_of_a_kind(&mut cards);
         assert!(tk.is_some());
@@ -552,11 +598,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 0, num: 3 },
-                    Card { shape: 2, num: 3 },
-                    Card { shape: 3, num: 3 },
-                    Card { shape: 2, num: 12 },
-                    Card { shape: 0, num: 11 }
+                    Card::new(Rank::Five, Suit::Spade),
+                    Card::new(Rank::Five, Suit::Heart),
+                    Card::new(Rank::Five, Suit::Club),
+                    Card::new(Rank::Ace, Suit::Heart),
+                    Card::new(Rank::King, Suit::Spade)
                 ])
             );
         }
@@ -565,13 +611,13 @@ mod test_for_grade {
     #[test]
     fn test_two_pairs() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 1 },
-            Card { shape: 0, num: 12 },
-            Card { shape: 1, num: 7 },
-            Card { shape: 1, num: 12 },
-            Card { shape: 2, num: 7 },
-            Card { shape: 2, num: 10 },
-            Card { shape: 3, num: 10 },
+            Card::new(Rank::Three, Suit::Spade),
+            Card::new(Rank::Ace, Suit::Spade),
+            Card::new(Rank::Nine, Suit::Diamond),
+            Card::new(Rank::Ace, Suit::Diamond),
+            Card::new(Rank::Nine, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Club),
         ];
         let tp = Grade::is_two_pairs(&mut cards);
         assert!(tp.is_some());
@@ -579,11 +625,11 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 3, num: 10 },
-                    Card { shape: 2, num: 10 },
-                    Card { shape: 2, num: 7 },
-                    Card { shape: 0, num: 12 },
-                    Card { shape: 1, num: 12 }
+                    Card::new(Rank::Queen, Suit::Club),
+                    Card::new(Rank::Queen, Suit::Heart),
+                    Card::new(Rank::Nine, Suit::Heart),
+                    Card::new(Rank::Ace, Suit::Spade),
+                    Card::new(Rank::Ace, Suit::Diamond)
                 ])
             );
         }
@@ -591,13 +637,13 @@ mod test_for_grade {
     #[test]
     fn test_one_pair() {
         let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 1 },
-            Card { shape: 0, num: 11 },
-            Card { shape: 1, num: 7 },
-            Card { shape: 1, num: 12 },
-            Card { shape: 2, num: 7 },
-            Card { shape: 2, num: 2 },
-            Card { shape: 3, num: 10 },
+            Card::new(Rank::Three, Suit::Spade),
+            Card::new(Rank::King, Suit::Spade),
+            Card::new(Rank::Nine, Suit::Diamond),
+            Card::new(Rank::Ace, Suit::Diamond),
+            Card::new(Rank::Nine, Suit::Heart),
+            Card::new(Rank::Four, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Club),
         ];
         let op = Grade::is_one_pair(&mut cards);
         assert!(op.is_some());
@@ -605,40 +651,231 @@ mod test_for_grade {
             assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 1, num: 12 },
-                    Card { shape: 0, num: 11 },
-                    Card { shape: 2, num: 7 },
-                    Card { shape: 1, num: 7 },
-                    Card { shape: 3, num: 10 }
+                    Card::new(Rank::Ace, Suit::Diamond),
+                    Card::new(Rank::King, Suit::Spade),
+                    Card::new(Rank::Nine, Suit::Heart),
+                    Card::new(Rank::Nine, Suit::Diamond),
+                    Card::new(Rank::Queen, Suit::Club)
                 ])
             );
         }
     }
 
+    #[test]
+    fn test_from_set() {
+        let cards = parse_cards("As Ks Qs Js Ts 2d 3c").unwrap();
+        let set: CardSet = cards.iter().collect();
+        assert!(matches!(Grade::from_set(set), Grade::StraightFlush(_)));
+        assert!(Grade::from_set(set) == Grade::new(&cards));
+    }
+
+    #[test]
+    fn test_wheel() {
+        // A-2-3-4-5 is a straight, five high.
+        match Grade::new(&parse_cards("As 2d 3c 4h 5s 9d Kc").unwrap()) {
+            Grade::Straight(cards) => {
+                assert_eq!(cards.map(|card| card.rank), [Rank::Five, Rank::Four, Rank::Three, Rank::Two, Rank::Ace])
+            }
+            grade => panic!("{:?} is not a straight", grade),
+        }
+        // 2-3-4-5 without an ace or a six is not.
+        let grade = Grade::new(&parse_cards("Ks 2d 3c 4h 5s 9d Jc").unwrap());
+        assert!(matches!(grade, Grade::Top(_)));
+    }
+
+    #[test]
+    fn test_rank_round_trip() {
+        for hand in [
+            "As Ks Qs Js Ts 2d 3c",
+            "5h 4h 3h 2h Ah Kh 9c",
+            "Ah Ad Ac Kh Kd Kc 2s",
+            "9h 9d 5c 5s 3h 3d Kc",
+            "9h 9d 5c 5s 3h 3d 2c",
+            "2s 3s 4s 5s 2d 3d 6c",
+            "6h 5d 4c 3s 2h Ad 9c",
+            "Ah Kh 7h 2h 9c Qh Qd",
+            "Jh 8d 7c 5s 3h 2d Kc",
+        ] {
+            let cards = parse_cards(hand).unwrap();
+            let set: CardSet = cards.iter().collect();
+            let rank = crate::evaluator::evaluate(set);
+            let grade = Grade::from_rank(rank, set);
+            assert!(grade == Grade::new(&cards), "{}", hand);
+            assert_eq!(grade.rank(), rank);
+            assert_eq!(Grade::new(&cards).rank(), rank);
+            assert!(grade.cards().iter().all(|&card| set.contains(card)));
+        }
+    }
+
     #[test]
     fn test_top() {
-        let mut cards: Vec<Card> = vec![
-            Card { shape: 0, num: 1 },
-            Card { shape: 0, num: 3 },
-            Card { shape: 1, num: 5 },
-            Card { shape: 1, num: 7 },
-            Card { shape: 2, num: 8 },
-            Card { shape: 2, num: 10 },
-            Card { shape: 3, num: 11 },
+        let cards: Vec<Card> = vec![
+            Card::new(Rank::Three, Suit::Spade),
+            Card::new(Rank::Five, Suit::Spade),
+            Card::new(Rank::Seven, Suit::Diamond),
+            Card::new(Rank::Nine, Suit::Diamond),
+            Card::new(Rank::Ten, Suit::Heart),
+            Card::new(Rank::Queen, Suit::Heart),
+            Card::new(Rank::King, Suit::Club),
         ];
-        let top = Grade::new(&mut cards);
+        let top = Grade::new(&cards);
         match top {
             Grade::Top(cards) => assert_eq!(
                 HashSet::from(cards),
                 HashSet::from([
-                    Card { shape: 3, num: 11 },
-                    Card { shape: 2, num: 10 },
-                    Card { shape: 2, num: 8 },
-                    Card { shape: 1, num: 7 },
-                    Card { shape: 1, num: 5 }
+                    Card::new(Rank::King, Suit::Club),
+                    Card::new(Rank::Queen, Suit::Heart),
+                    Card::new(Rank::Ten, Suit::Heart),
+                    Card::new(Rank::Nine, Suit::Diamond),
+                    Card::new(Rank::Seven, Suit::Diamond)
                 ])
             ),
             _ => panic!("cards do not constitute top."),
         }
     }
 }
+
+// walks every 7-card hand, which takes minutes even in release :
+// cargo test --release -- --ignored exhaustive
+#[cfg(test)]
+mod exhaustive_test {
+    use super::*;
+    use crate::deck::*;
+    use crate::evaluator::evaluate;
+
+    type Value = (u8, Rank, Rank, Rank, Rank, Rank);
+
+    // published number of 7-card hands per category, weakest first.
+    const FREQUENCIES: [u64; 9] = [
+        23_294_460, 58_627_800, 31_433_400, 6_461_620, 6_180_020, 4_047_644, 3_473_184, 224_848,
+        41_584,
+    ];
+
+    // value of exactly five cards, found without any of Grade's detectors.
+    fn five_card_value(five: &[Card; 5]) -> Value {
+        let mut cnts = [0usize; 13];
+        five.iter().for_each(|card| cnts[card.rank.index()] += 1);
+        // ranks ordered by how many times they appear, then by rank, highest first.
+        let mut ranks = [Rank::Two; 5];
+        let mut i = 0;
+        for cnt in (1..=4).rev() {
+            for num in (0..13).rev().filter(|&num| cnts[num] == cnt) {
+                ranks[i..i + cnt].fill(Rank::ALL[num]);
+                i += cnt;
+            }
+        }
+        let distinct = cnts.iter().filter(|&&cnt| cnt > 0).count();
+        let max_cnt = *cnts.iter().max().unwrap();
+        let flush = five.iter().all(|card| card.suit == five[0].suit);
+        let wheel = ranks == [Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two];
+        let straight = distinct == 5 && (ranks[0].index() - ranks[4].index() == 4 || wheel);
+        if wheel {
+            ranks.rotate_left(1);
+        }
+        let grade = match (flush, straight, max_cnt, distinct) {
+            (true, true, _, _) => 8,
+            (_, _, 4, _) => 7,
+            (_, _, 3, 2) => 6,
+            (true, false, _, _) => 5,
+            (false, true, _, _) => 4,
+            (_, _, 3, _) => 3,
+            (_, _, 2, 3) => 2,
+            (_, _, 2, _) => 1,
+            _ => 0,
+        };
+        (grade, ranks[0], ranks[1], ranks[2], ranks[3], ranks[4])
+    }
+
+    // tries every five of the seven cards.
+    fn brute_force_value(cards: &[Card; 7]) -> Value {
+        let mut best = (0, Rank::Two, Rank::Two, Rank::Two, Rank::Two, Rank::Two);
+        for skip1 in 0..7 {
+            for skip2 in skip1 + 1..7 {
+                let mut five = [cards[0]; 5];
+                let kept = (0..7).filter(|&i| i != skip1 && i != skip2);
+                kept.enumerate().for_each(|(j, i)| five[j] = cards[i]);
+                best = best.max(five_card_value(&five));
+            }
+        }
+        best
+    }
+
+    // calls visit with every 7 cards out of the deck.
+    fn for_each_seven_cards(mut visit: impl FnMut(&[Card; 7])) {
+        let deck = Deck::new();
+        let mut idx: [usize; 7] = [0, 1, 2, 3, 4, 5, 6];
+        loop {
+            visit(&idx.map(|i| deck.cards[i]));
+            let mut i = 6;
+            while idx[i] == 52 - 7 + i {
+                if i == 0 {
+                    return;
+                }
+                i -= 1;
+            }
+            idx[i] += 1;
+            for j in i + 1..7 {
+                idx[j] = idx[j - 1] + 1;
+            }
+        }
+    }
+
+    #[test]
+    fn five_card_reference_is_sane() {
+        let value = |hand: &str| {
+            let cards = parse_cards(hand).unwrap();
+            brute_force_value(&std::array::from_fn(|i| cards[i]))
+        };
+        assert_eq!(value("5s 4d 3h 2c As 9d Kc").0, 4);
+        assert!(value("5s 4d 3h 2c As 9d Kc") < value("6s 5d 4h 3c 2s 9d Kc"));
+        assert_eq!(value("9h 9d 5c 5s 3h 3d Kc").1, Rank::Nine);
+        assert_eq!(value("9h 9d 5c 5s 3h 3d Kc").5, Rank::King);
+        assert_eq!(value("Ah Ad Ac Kh Kd Kc 2s").0, 6);
+    }
+
+    #[test]
+    #[ignore]
+    fn exhaustive_seven_card_hands() {
+        let mut category_cnts = [0u64; 9];
+        let mut class_values: Vec<Option<Value>> = vec![None; 7463];
+        let mut disagreements = 0u64;
+        let mut unequal_ties = 0u64;
+        for_each_seven_cards(|cards| {
+            let grade = Grade::new(cards);
+            let value = grade.as_value();
+            let rank = evaluate(cards.iter().collect());
+            category_cnts[value.0 as usize] += 1;
+
+            let reference = brute_force_value(cards);
+            if value != reference {
+                if disagreements < 20 {
+                    println!("{:?} : grade {} but best five is {:?}", cards, grade, reference);
+                }
+                disagreements += 1;
+            }
+            match class_values[rank.value() as usize] {
+                None => class_values[rank.value() as usize] = Some(value),
+                Some(tied) if tied != value => {
+                    if unequal_ties < 20 {
+                        println!("{:?} : grade {} differs from a tied hand", cards, grade);
+                    }
+                    unequal_ties += 1;
+                }
+                _ => (),
+            }
+        });
+        println!("category counts : {:?}", category_cnts);
+        println!("disagreements with brute force : {}", disagreements);
+        println!("ties not graded as equal : {}", unequal_ties);
+
+        assert_eq!(category_cnts, FREQUENCIES);
+        assert_eq!(category_cnts.iter().sum::<u64>(), 133_784_560);
+        assert_eq!(disagreements, 0);
+        assert_eq!(unequal_ties, 0);
+        // 4824 of the 7462 classes can be the best five of seven cards,
+        // and Grade orders them the same way as their ranks.
+        let values: Vec<Value> = class_values.iter().flatten().cloned().collect();
+        assert_eq!(values.len(), 4824);
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -1,6 +1,7 @@
-use std::cmp;
 use std::fmt;
-use crate::deck::Card;
+use std::str::FromStr;
+use crate::cardset::CardSet;
+use crate::deck::{parse_cards, Card, ParseCardError};
 
 #[derive(fmt::Debug, Clone)]
 pub struct Hand {
@@ -14,6 +15,12 @@ impl fmt::Display for Hand {
     }
 }
 
+impl Default for Hand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Hand {
     pub fn new() -> Self {
         Self {
@@ -21,22 +28,16 @@ impl Hand {
 	    cards: Vec::<Card>::new(),
         }
     }
-    pub fn clone(&self) -> Self {
-	let cards_cnt = self.cards_cnt;
-	let cards = self.cards.clone();
-	Self {cards_cnt, cards}
-    }
-
-    pub fn add_card(&mut self, card: Card) -> () {
+    pub fn add_card(&mut self, card: Card) {
         if self.cards_cnt < 7 {
 	    self.cards.push(card);
 	    self.cards_cnt += 1;
 	}
     }
 
-    pub fn add_cards(&mut self, cards: &[Card]) -> () {
+    pub fn add_cards(&mut self, cards: &[Card]) {
         //    if self.cards_cnt < 7 {
-        cards.into_iter().for_each(|card| {
+        cards.iter().for_each(|card| {
             if self.cards_cnt < 7 {
                 self.cards.push(*card);
                 self.cards_cnt += 1;
@@ -46,6 +47,42 @@ impl Hand {
     pub fn len(&self) -> usize {
         self.cards_cnt
     }
+    pub fn is_empty(&self) -> bool {
+        self.cards_cnt == 0
+    }
+    pub fn card_set(&self) -> CardSet {
+        self.cards.iter().collect()
+    }
+}
+
+impl From<CardSet> for Hand {
+    fn from(set: CardSet) -> Self {
+        let mut hand = Hand::new();
+        hand.add_cards(&set.to_vec());
+        hand
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_cards(s)?;
+        if cards.len() > 7 {
+            return Err(ParseCardError::TooManyCards(cards.len()));
+        }
+        let mut hand = Hand::new();
+        hand.add_cards(&cards);
+        Ok(hand)
+    }
+}
+
+impl TryFrom<&str> for Hand {
+    type Error = ParseCardError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
 }
 
 #[cfg(test)]
@@ -58,6 +95,23 @@ mod test_for_deck {
         let mut p1 = Hand::new();
         p1.add_cards(&deck.deal_cards(7));
         assert_eq!(p1.len(), 7);
-        assert_eq!(p1.cards[6], Card{shape:0, num:6});
+        assert_eq!(p1.cards[6], Card::new(Rank::Eight, Suit::Spade));
+    }
+    #[test]
+    fn hand_from_str() {
+        let hand: Hand = "As Kd 7c".parse().unwrap();
+        assert_eq!(hand.len(), 3);
+        assert_eq!(hand.cards[1], Card::new(Rank::King, Suit::Diamond));
+        assert_eq!(
+            Hand::try_from("2s3s4s5s6s7s8s9s").map(|hand| hand.len()),
+            Err(ParseCardError::TooManyCards(8))
+        );
+    }
+    #[test]
+    fn hand_from_card_set() {
+        let set: CardSet = parse_cards("As Kd 7c").unwrap().iter().collect();
+        let hand = Hand::from(set);
+        assert_eq!(hand.len(), 3);
+        assert_eq!(hand.card_set(), set);
     }
 }
@@ -0,0 +1,1062 @@
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::betting::{Action, ActionRecord, HoldemTable};
+use crate::cardset::CardSet;
+use crate::deck::{parse_cards, Card, Rank};
+use crate::evaluator::HandCategory;
+use crate::grade::Grade;
+use crate::hand::Hand;
+use crate::holdem::Stage;
+use crate::pot::{award_pots, build_pots, OddChipRule, Pot};
+
+// what a hand history needs besides the hand itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandInfo {
+    pub hand_id: u64,
+    pub table_name: String,
+    pub date: String,
+    // one name per seat. seats without a name are called Player1, Player2 and so on.
+    pub names: Vec<String>,
+    // the seat whose hole cards are shown, every seat's when none.
+    pub hero: Option<usize>,
+}
+
+impl HandInfo {
+    pub fn new(hand_id: u64) -> Self {
+        Self {
+            hand_id,
+            table_name: String::from("poker_env"),
+            date: String::from("1970/01/01 0:00:00 ET"),
+            names: Vec::new(),
+            hero: None,
+        }
+    }
+
+    pub fn with_table_name(mut self, table_name: &str) -> Self {
+        self.table_name = table_name.to_string();
+        self
+    }
+
+    pub fn with_date(mut self, date: &str) -> Self {
+        self.date = date.to_string();
+        self
+    }
+
+    pub fn with_names(mut self, names: Vec<String>) -> Self {
+        self.names = names;
+        self
+    }
+
+    pub fn with_hero(mut self, hero: usize) -> Self {
+        self.hero = Some(hero);
+        self
+    }
+
+    pub fn name(&self, seat: usize) -> String {
+        self.names.get(seat).cloned().unwrap_or_else(|| format!("Player{}", seat + 1))
+    }
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.to_text()).collect::<Vec<String>>().join(" ")
+}
+
+fn rank_name(rank: Rank) -> String {
+    format!("{:?}", rank)
+}
+
+fn rank_plural(rank: Rank) -> String {
+    match rank {
+        Rank::Six => String::from("Sixes"),
+        _ => format!("{:?}s", rank),
+    }
+}
+
+// the PokerStars wording of a made hand, such as "two pair, Kings and Nines".
+pub fn describe_grade(grade: &Grade) -> String {
+    let rank = grade.rank();
+    let ranks = rank.ranks();
+    match rank.category() {
+        HandCategory::StraightFlush if ranks[0] == Rank::Ace => String::from("a Royal Flush"),
+        HandCategory::StraightFlush => format!("a straight flush, {} to {}", rank_name(ranks[4]), rank_name(ranks[0])),
+        HandCategory::FourOfAKind => format!("four of a kind, {}", rank_plural(ranks[0])),
+        HandCategory::FullHouse => format!("a full house, {} full of {}", rank_plural(ranks[0]), rank_plural(ranks[3])),
+        HandCategory::Flush => format!("a flush, {} high", rank_name(ranks[0])),
+        HandCategory::Straight => format!("a straight, {} to {}", rank_name(ranks[4]), rank_name(ranks[0])),
+        HandCategory::ThreeOfAKind => format!("three of a kind, {}", rank_plural(ranks[0])),
+        HandCategory::TwoPairs => format!("two pair, {} and {}", rank_plural(ranks[0]), rank_plural(ranks[2])),
+        HandCategory::OnePair => format!("a pair of {}", rank_plural(ranks[0])),
+        HandCategory::Top => format!("high card {}", rank_name(ranks[0])),
+    }
+}
+
+fn street_name(stage: Stage) -> &'static str {
+    match stage {
+        Stage::PreFlop => "Flop",
+        Stage::Flop => "the Flop",
+        Stage::Turn => "the Turn",
+        Stage::River => "the River",
+    }
+}
+
+// writes a finished hand as a PokerStars hand history. chips are play money, without a
+// currency sign.
+pub fn export_pokerstars(table: &HoldemTable, info: &HandInfo) -> Result<String, &'static str> {
+    if !table.is_finished() {return Err("The hand is not over yet.");}
+    let seats = table.seats();
+    let names: Vec<String> = (0..seats).map(|seat| info.name(seat)).collect();
+    let blinds = table.blinds();
+    let button = table.button();
+    let (small_blind, big_blind) = table.blind_seats();
+    let board = table.game().board_cards();
+    // hole cards high card first.
+    let holes: Vec<Vec<Card>> = (0..seats)
+        .map(|seat| {
+            let mut cards = table.game().hole_cards(seat).to_vec();
+            cards.sort_by_key(|card| std::cmp::Reverse(card.rank));
+            cards
+        })
+        .collect();
+    let contributions = table.contributions();
+    let mut out = String::new();
+    let mut line = |text: String| {
+        out.push_str(&text);
+        out.push('\n');
+    };
+
+    line(format!(
+        "PokerStars Hand #{}:  Hold'em {} ({}/{}) - {}",
+        info.hand_id,
+        table.structure().name(),
+        blinds.small_blind,
+        blinds.big_blind,
+        info.date
+    ));
+    line(format!("Table '{}' {}-max Seat #{} is the button", info.table_name, seats, button + 1));
+    for (seat, name) in names.iter().enumerate() {
+        line(format!("Seat {}: {} ({} in chips)", seat + 1, name, table.starting_stacks()[seat]));
+    }
+
+    // the posts, replayed from the starting stacks.
+    let mut stacks = table.starting_stacks().to_vec();
+    let all_in = |stack: u64| if stack == 0 { " and is all-in" } else { "" };
+    if blinds.ante > 0 {
+        for seat in 0..seats {
+            let ante = blinds.ante.min(stacks[seat]);
+            stacks[seat] -= ante;
+            line(format!("{}: posts the ante {}{}", names[seat], ante, all_in(stacks[seat])));
+        }
+    }
+    for (seat, blind, kind) in [(small_blind, blinds.small_blind, "small"), (big_blind, blinds.big_blind, "big")] {
+        let blind = blind.min(stacks[seat]);
+        stacks[seat] -= blind;
+        line(format!("{}: posts {} blind {}{}", names[seat], kind, blind, all_in(stacks[seat])));
+    }
+
+    line(String::from("*** HOLE CARDS ***"));
+    for seat in (0..seats).filter(|&seat| info.hero.is_none_or(|hero| hero == seat)) {
+        line(format!("Dealt to {} [{}]", names[seat], cards_text(&holes[seat])));
+    }
+
+    // the seat that put in the most gets back what nobody matched.
+    let mut by_size: Vec<usize> = (0..seats).collect();
+    by_size.sort_by_key(|&seat| std::cmp::Reverse(contributions[seat]));
+    let uncalled = (contributions[by_size[0]] - contributions[by_size[1]], by_size[0]);
+
+    let mut folded_on: Vec<Option<Stage>> = vec![None; seats];
+    let history = table.history();
+    for (street, stage) in [Stage::PreFlop, Stage::Flop, Stage::Turn, Stage::River].into_iter().enumerate() {
+        match street {
+            1 if board.len() >= 3 => line(format!("*** FLOP *** [{}]", cards_text(&board[..3]))),
+            2 if board.len() >= 4 => line(format!("*** TURN *** [{}] [{}]", cards_text(&board[..3]), board[3].to_text())),
+            3 if board.len() >= 5 => line(format!("*** RIVER *** [{}] [{}]", cards_text(&board[..4]), board[4].to_text())),
+            0 => (),
+            _ => break,
+        }
+        let mut current_bet = if stage == Stage::PreFlop { blinds.big_blind } else { 0 };
+        for (i, record) in history.iter().enumerate().filter(|(_, record)| record.stage == stage) {
+            let name = &names[record.seat];
+            stacks[record.seat] -= record.amount;
+            let suffix = all_in(stacks[record.seat]);
+            match record.action {
+                Action::Fold => {
+                    folded_on[record.seat] = Some(stage);
+                    line(format!("{}: folds", name));
+                }
+                Action::Check => line(format!("{}: checks", name)),
+                Action::Call => line(format!("{}: calls {}{}", name, record.amount, suffix)),
+                Action::Bet(to) => {
+                    current_bet = to;
+                    line(format!("{}: bets {}{}", name, to, suffix));
+                }
+                Action::Raise(to) => {
+                    line(format!("{}: raises {} to {}{}", name, to - current_bet, to, suffix));
+                    current_bet = to;
+                }
+            }
+            // the bet comes back right after the last action, before any board runs out.
+            if i + 1 == history.len() && uncalled.0 > 0 {
+                line(format!("Uncalled bet ({}) returned to {}", uncalled.0, names[uncalled.1]));
+            }
+        }
+        // with the blinds putting everyone all-in, nobody acts at all.
+        if history.is_empty() && stage == Stage::PreFlop && uncalled.0 > 0 {
+            line(format!("Uncalled bet ({}) returned to {}", uncalled.0, names[uncalled.1]));
+        }
+    }
+
+    // what each pot pays once the uncalled bet is back.
+    let mut pots: Vec<Pot> = table.pots();
+    if let Some(last) = pots.last_mut() {
+        last.amount -= uncalled.0;
+    }
+    pots.retain(|pot| pot.amount > 0);
+    let showdown = (0..seats).filter(|&seat| !table.is_folded(seat)).count() > 1;
+    let grades: Vec<Option<Grade>> = (0..seats)
+        .map(|seat| (showdown && !table.is_folded(seat)).then(|| Grade::from_set(table.game().players()[seat].card_set())))
+        .collect();
+    let pot_name = |i: usize| match (pots.len(), i) {
+        (1, _) => String::from("pot"),
+        (_, 0) => String::from("main pot"),
+        (2, _) => String::from("side pot"),
+        (_, i) => format!("side pot-{}", i),
+    };
+    if showdown {
+        line(String::from("*** SHOW DOWN ***"));
+        for seat in (0..seats).filter(|&seat| !table.is_folded(seat)) {
+            let description = describe_grade(grades[seat].as_ref().unwrap());
+            line(format!("{}: shows [{}] ({})", names[seat], cards_text(&holes[seat]), description));
+        }
+    }
+    let mut won = vec![0; seats];
+    for (i, pot) in pots.iter().enumerate() {
+        let awarded = award_pots(std::slice::from_ref(pot), &grades, button, table.odd_chip_rule())?;
+        for (seat, &chips) in awarded.iter().enumerate().filter(|(_, &chips)| chips > 0) {
+            won[seat] += chips;
+            line(format!("{} collected {} from {}", names[seat], chips, pot_name(i)));
+        }
+    }
+    if !showdown {
+        let winner = (0..seats).find(|&seat| !table.is_folded(seat)).unwrap();
+        line(format!("{}: doesn't show hand", names[winner]));
+    }
+
+    line(String::from("*** SUMMARY ***"));
+    let total: u64 = pots.iter().map(|pot| pot.amount).sum();
+    let mut total_line = format!("Total pot {}", total);
+    // writing to a String never fails.
+    if pots.len() > 1 {
+        for (i, pot) in pots.iter().enumerate() {
+            let mut name = pot_name(i);
+            name.replace_range(..1, &name[..1].to_uppercase());
+            let _ = write!(total_line, " {} {}.", name, pot.amount);
+        }
+    }
+    line(format!("{} | Rake 0", total_line));
+    if !board.is_empty() {
+        line(format!("Board [{}]", cards_text(board)));
+    }
+    for seat in 0..seats {
+        let mut text = format!("Seat {}: {}", seat + 1, names[seat]);
+        if seat == button {
+            text.push_str(" (button)");
+        }
+        if seat == small_blind {
+            text.push_str(" (small blind)");
+        } else if seat == big_blind {
+            text.push_str(" (big blind)");
+        }
+        match folded_on[seat] {
+            Some(stage) => {
+                let _ = write!(text, " folded {} {}", if stage == Stage::PreFlop { "before" } else { "on" }, street_name(stage));
+                if stage == Stage::PreFlop && contributions[seat] == blinds.ante.min(table.starting_stacks()[seat]) {
+                    text.push_str(" (didn't bet)");
+                }
+            }
+            None if showdown => {
+                let description = describe_grade(grades[seat].as_ref().unwrap());
+                let _ = match won[seat] {
+                    0 => write!(text, " showed [{}] and lost with {}", cards_text(&holes[seat]), description),
+                    chips => write!(text, " showed [{}] and won ({}) with {}", cards_text(&holes[seat]), chips, description),
+                };
+            }
+            None => {
+                let _ = write!(text, " collected ({})", won[seat]);
+            }
+        }
+        line(text);
+    }
+    Ok(out)
+}
+
+// a player sitting at the table when a recorded hand starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryPlayer {
+    // the seat number as written, from 1.
+    pub seat: usize,
+    pub name: String,
+    pub stack: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PostKind {
+    Ante,
+    SmallBlind,
+    BigBlind,
+    // a dead small blind with a live big blind, posted together.
+    SmallAndBigBlind,
+    Straddle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Post {
+    pub player: usize,
+    pub kind: PostKind,
+    pub amount: u64,
+}
+
+// a hand read back from a PokerStars hand history. players are numbered in seat order and
+// real money amounts are in cents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    pub hand_id: u64,
+    pub structure: String,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub date: String,
+    pub table_name: String,
+    pub max_seats: usize,
+    // the player on the button, none when the button seat is empty.
+    pub button: Option<usize>,
+    // the button's seat number as written, taken or not.
+    pub button_seat: usize,
+    pub players: Vec<HistoryPlayer>,
+    pub posts: Vec<Post>,
+    // hole cards dealt to the hero or shown down, per player.
+    pub hole_cards: Vec<Option<Vec<Card>>>,
+    // seats of the records are players, amounts the chips each action put in.
+    pub actions: Vec<ActionRecord>,
+    pub board: Vec<Card>,
+    pub uncalled: Option<(usize, u64)>,
+    pub showdown: bool,
+    // chips each player took from the pots, after the rake.
+    pub collected: Vec<(usize, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseHistoryError {
+    Empty,
+    InvalidHeader(String),
+    InvalidLine(String),
+    InvalidCards(String),
+}
+
+impl fmt::Display for ParseHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no hand history"),
+            Self::InvalidHeader(line) => write!(f, "invalid hand history header: {:?}", line),
+            Self::InvalidLine(line) => write!(f, "invalid hand history line: {:?}", line),
+            Self::InvalidCards(cards) => write!(f, "invalid cards in hand history: {:?}", cards),
+        }
+    }
+}
+
+impl std::error::Error for ParseHistoryError {}
+
+// chips as written. amounts with a currency sign turn into cents.
+fn parse_amount(s: &str) -> Option<u64> {
+    let s = s.trim();
+    match s.strip_prefix(['$', '€', '£']) {
+        Some(money) => {
+            let (whole, cents) = money.split_once('.').unwrap_or((money, "0"));
+            if cents.len() > 2 {
+                return None;
+            }
+            Some(whole.parse::<u64>().ok()? * 100 + format!("{:0<2}", cents).parse::<u64>().ok()?)
+        }
+        None => s.parse().ok(),
+    }
+}
+
+fn parse_bracketed(line: &str) -> Result<Vec<Card>, ParseHistoryError> {
+    let mut cards = Vec::new();
+    for part in line.split('[').skip(1) {
+        let inside = part.split(']').next().unwrap_or("");
+        cards.extend(parse_cards(inside).map_err(|_| ParseHistoryError::InvalidCards(inside.to_string()))?);
+    }
+    Ok(cards)
+}
+
+// the player whose name, followed by `separator`, starts the line, and the rest of it.
+// names may hold spaces, so the longest match wins.
+fn player_prefix<'a>(players: &[HistoryPlayer], line: &'a str, separator: &str) -> Option<(usize, &'a str)> {
+    players
+        .iter()
+        .enumerate()
+        .filter_map(|(player, seat)| line.strip_prefix(seat.name.as_str())?.strip_prefix(separator).map(|rest| (player, rest)))
+        .max_by_key(|(player, _)| players[*player].name.len())
+}
+
+// lines which change nothing, whoever they are about : chat, players joining or leaving the
+// table and connection notices.
+fn is_harmless(line: &str) -> bool {
+    line.contains(" said, \"")
+        || line.contains(" joins the table at seat #")
+        || [" leaves the table", " is connected", " is disconnected"].iter().any(|notice| line.ends_with(notice))
+}
+
+impl FromStr for HandHistory {
+    type Err = ParseHistoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(|line| line.trim_start_matches('\u{feff}').trim()).filter(|line| !line.is_empty());
+        let header = lines.next().ok_or(ParseHistoryError::Empty)?;
+        let invalid_header = || ParseHistoryError::InvalidHeader(header.to_string());
+        let (hand_id, game) = header
+            .strip_prefix("PokerStars ")
+            .and_then(|rest| rest.split_once("Hand #"))
+            .and_then(|(_, rest)| rest.split_once(':'))
+            .and_then(|(id, rest)| Some((id.trim().parse::<u64>().ok()?, rest.split_once("Hold'em ")?.1)))
+            .ok_or_else(invalid_header)?;
+        let structure = game.split(' ').take_while(|word| !word.starts_with('(') && *word != "-").collect::<Vec<&str>>().join(" ");
+        let (small_blind, big_blind) = game
+            .split('(')
+            .skip(1)
+            .find_map(|part| {
+                let (small, big) = part.split(')').next()?.split_once('/')?;
+                Some((parse_amount(small)?, parse_amount(big.split_whitespace().next()?)?))
+            })
+            .ok_or_else(invalid_header)?;
+        // tournaments put the level between the game and the date.
+        let date = game.rsplit_once(" - ").map_or("", |(_, date)| date).to_string();
+
+        let table = lines.next().ok_or_else(invalid_header)?;
+        let invalid_table = || ParseHistoryError::InvalidLine(table.to_string());
+        let (table_name, seating) = table
+            .strip_prefix("Table '")
+            .and_then(|rest| rest.rsplit_once('\''))
+            .ok_or_else(invalid_table)?;
+        let max_seats = seating
+            .split_whitespace()
+            .find_map(|word| word.strip_suffix("-max")?.parse().ok())
+            .ok_or_else(invalid_table)?;
+        let button_seat: usize = seating
+            .split_once("Seat #")
+            .and_then(|(_, rest)| rest.split_whitespace().next()?.parse().ok())
+            .ok_or_else(invalid_table)?;
+
+        let mut history = Self {
+            hand_id,
+            structure,
+            small_blind,
+            big_blind,
+            date,
+            table_name: table_name.to_string(),
+            max_seats,
+            button: None,
+            button_seat,
+            players: Vec::new(),
+            posts: Vec::new(),
+            hole_cards: Vec::new(),
+            actions: Vec::new(),
+            board: Vec::new(),
+            uncalled: None,
+            showdown: false,
+            collected: Vec::new(),
+        };
+        let mut stage = Stage::PreFlop;
+        let mut street_bets: Vec<u64> = Vec::new();
+        let mut dealt = false;
+        for line in lines {
+            let invalid = || ParseHistoryError::InvalidLine(line.to_string());
+            if let Some(marker) = line.strip_prefix("*** ") {
+                let street = match marker.split(" ***").next().unwrap_or("") {
+                    "HOLE CARDS" => {
+                        dealt = true;
+                        street_bets.resize(history.players.len(), 0);
+                        None
+                    }
+                    "FLOP" => Some(Stage::Flop),
+                    "TURN" => Some(Stage::Turn),
+                    "RIVER" => Some(Stage::River),
+                    "SHOW DOWN" => {
+                        history.showdown = true;
+                        None
+                    }
+                    "SUMMARY" => break,
+                    _ => return Err(invalid()),
+                };
+                if let Some(street) = street {
+                    stage = street;
+                    history.board = parse_bracketed(line)?;
+                    street_bets.iter_mut().for_each(|bet| *bet = 0);
+                }
+                continue;
+            }
+            if !dealt {
+                if let Some(rest) = line.strip_prefix("Seat ") {
+                    let (seat, rest) = rest.split_once(": ").ok_or_else(invalid)?;
+                    let chips_at = rest.rfind(" in chips").ok_or_else(invalid)?;
+                    let open = rest[..chips_at].rfind(" (").ok_or_else(invalid)?;
+                    let seat: usize = seat.parse().map_err(|_| invalid())?;
+                    if seat == button_seat {
+                        history.button = Some(history.players.len());
+                    }
+                    history.players.push(HistoryPlayer {
+                        seat,
+                        name: rest[..open].to_string(),
+                        stack: parse_amount(&rest[open + 2..chips_at]).ok_or_else(invalid)?,
+                    });
+                    history.hole_cards.push(None);
+                    street_bets.push(0);
+                    continue;
+                }
+            }
+            let players = &history.players;
+            if let Some((player, _)) = line.strip_prefix("Dealt to ").and_then(|rest| player_prefix(players, rest, " [")) {
+                history.hole_cards[player] = Some(parse_bracketed(line)?);
+            } else if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+                let (amount, name) = rest.split_once(") returned to ").ok_or_else(invalid)?;
+                let player = players.iter().position(|seat| seat.name == name).ok_or_else(invalid)?;
+                history.uncalled = Some((player, parse_amount(amount).ok_or_else(invalid)?));
+            } else if let Some((player, rest)) = player_prefix(players, line, " collected ") {
+                let amount = rest.split_whitespace().next().and_then(parse_amount).ok_or_else(invalid)?;
+                history.collected.push((player, amount));
+            } else if let Some((player, rest)) = player_prefix(players, line, ": ") {
+                // drops " and is all-in" and the like.
+                let rest = rest.split(" and ").next().unwrap_or(rest);
+                let words: Vec<&str> = rest.split_whitespace().collect();
+                let amount = |i: usize| words.get(i).and_then(|word| parse_amount(word)).ok_or_else(invalid);
+                let action = match words[..] {
+                    ["posts", "small", "blind", _]
+                    | ["posts", "big", "blind", _]
+                    | ["posts", "the", "ante", _]
+                    | ["posts", "small", "&", "big", "blinds", _]
+                    | ["posts", "straddle", _] => {
+                        let amount = amount(words.len() - 1)?;
+                        let (kind, live) = match words[1..words.len() - 1] {
+                            ["small", "blind"] => (PostKind::SmallBlind, amount),
+                            ["big", "blind"] => (PostKind::BigBlind, amount),
+                            ["small", "&", "big", "blinds"] => (PostKind::SmallAndBigBlind, amount.min(history.big_blind)),
+                            ["straddle"] => (PostKind::Straddle, amount),
+                            _ => (PostKind::Ante, 0),
+                        };
+                        street_bets[player] += live;
+                        history.posts.push(Post { player, kind, amount });
+                        None
+                    }
+                    ["folds", ..] => Some((Action::Fold, 0)),
+                    ["checks"] => Some((Action::Check, 0)),
+                    ["calls", _] => Some((Action::Call, amount(1)?)),
+                    ["bets", _] => Some((Action::Bet(amount(1)?), amount(1)?)),
+                    ["raises", _, "to", _] => {
+                        let to = amount(3)?;
+                        Some((Action::Raise(to), to.checked_sub(street_bets[player]).ok_or_else(invalid)?))
+                    }
+                    ["shows", ..] => {
+                        history.hole_cards[player] = Some(parse_bracketed(rest.split(']').next().unwrap_or(""))?);
+                        None
+                    }
+                    // lines which change nothing.
+                    ["mucks", ..]
+                    | ["doesn't", "show", ..]
+                    | ["is", "sitting", "out"]
+                    | ["sits", "out"]
+                    | ["has", "timed", "out", ..]
+                    | ["is", "disconnected"]
+                    | ["is", "connected"] => None,
+                    _ => return Err(invalid()),
+                };
+                if let Some((action, amount)) = action {
+                    street_bets[player] += amount;
+                    history.actions.push(ActionRecord { stage, seat: player, action, amount });
+                }
+            } else if !is_harmless(line) {
+                return Err(invalid());
+            }
+        }
+        if history.players.len() < 2 {
+            return Err(ParseHistoryError::InvalidHeader(header.to_string()));
+        }
+        Ok(history)
+    }
+}
+
+// splits a file of hand histories, as PokerStars writes them, and parses each one.
+pub fn parse_histories(text: &str) -> Result<Vec<HandHistory>, ParseHistoryError> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches('\u{feff}');
+        if trimmed.starts_with("PokerStars ") && trimmed.split(':').next().is_some_and(|head| head.contains("Hand #")) {
+            starts.push(offset + line.len() - trimmed.len());
+        }
+        offset += line.len();
+    }
+    if starts.is_empty() {
+        return Err(ParseHistoryError::Empty);
+    }
+    starts.push(text.len());
+    starts.windows(2).map(|bounds| text[bounds[0]..bounds[1]].parse()).collect()
+}
+
+// the table at the end of a street.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Street {
+    pub stage: Stage,
+    pub board: Vec<Card>,
+    pub pot: u64,
+    pub stacks: Vec<u64>,
+    pub folded: Vec<bool>,
+}
+
+// a recorded hand played again, with the winners our evaluator finds.
+#[derive(Debug)]
+pub struct Replay {
+    pub streets: Vec<Street>,
+    // the known hole cards of each player with the board.
+    pub hands: Vec<Option<Hand>>,
+    pub grades: Vec<Option<Grade>>,
+    // what each player should have taken from the pots, uncalled bets and rake left out.
+    pub winnings: Vec<u64>,
+    pub winners: Vec<usize>,
+    pub recorded_winners: Vec<usize>,
+}
+
+impl Replay {
+    pub fn winners_match(&self) -> bool {
+        self.winners == self.recorded_winners
+    }
+}
+
+impl HandHistory {
+    // replays the hand street by street and awards the pots with our evaluator. every seat
+    // showing down needs its hole cards known.
+    pub fn replay(&self) -> Result<Replay, &'static str> {
+        let players = self.players.len();
+        let mut stacks: Vec<u64> = self.players.iter().map(|player| player.stack).collect();
+        let mut contributions = vec![0; players];
+        let mut folded = vec![false; players];
+        let mut put_in = |stacks: &mut Vec<u64>, player: usize, amount: u64| -> Result<(), &'static str> {
+            stacks[player] = stacks[player].checked_sub(amount).ok_or("A player puts in more than the stack.")?;
+            contributions[player] += amount;
+            Ok(())
+        };
+        for post in &self.posts {
+            put_in(&mut stacks, post.player, post.amount)?;
+        }
+        let mut used = CardSet::new();
+        for card in self.board.iter().chain(self.hole_cards.iter().flatten().flatten()) {
+            if !used.insert(*card) {
+                return Err("A card shows up twice.");
+            }
+        }
+        let mut streets = Vec::new();
+        for (stage, cards) in [(Stage::PreFlop, 0), (Stage::Flop, 3), (Stage::Turn, 4), (Stage::River, 5)] {
+            if stage != Stage::PreFlop && self.board.len() < cards {
+                break;
+            }
+            for record in self.actions.iter().filter(|record| record.stage == stage) {
+                if folded[record.seat] {
+                    return Err("A folded player acts.");
+                }
+                put_in(&mut stacks, record.seat, record.amount)?;
+                folded[record.seat] |= record.action == Action::Fold;
+            }
+            streets.push(Street { stage, board: self.board[..cards].to_vec(), pot: 0, stacks: stacks.clone(), folded: folded.clone() });
+        }
+        for street in &mut streets {
+            street.pot = self.players.iter().zip(&street.stacks).map(|(player, stack)| player.stack - stack).sum();
+        }
+
+        let showdown = folded.iter().filter(|&&folded| !folded).count() > 1;
+        let hands: Vec<Option<Hand>> = self
+            .hole_cards
+            .iter()
+            .map(|cards| {
+                cards.as_ref().map(|cards| {
+                    let mut hand = Hand::new();
+                    hand.add_cards(cards);
+                    hand.add_cards(&self.board);
+                    hand
+                })
+            })
+            .collect();
+        if showdown && self.board.len() < 5 {
+            return Err("A showdown needs the whole board.");
+        }
+        let grades: Vec<Option<Grade>> = (0..players)
+            .map(|player| match &hands[player] {
+                Some(hand) if showdown && !folded[player] => Some(Grade::from_set(hand.card_set())),
+                _ => None,
+            })
+            .collect();
+        let pots = build_pots(&contributions, &folded);
+        // with the button seat empty, the odd chips start from the first player after it.
+        let button = self.button.unwrap_or_else(|| {
+            let next = self.players.iter().position(|player| player.seat > self.button_seat).unwrap_or(0);
+            (next + players - 1) % players
+        });
+        let mut winnings = award_pots(&pots, &grades, button, OddChipRule::LeftOfButton)?;
+        // the uncalled bet comes back to the player who made it.
+        let mut by_size: Vec<usize> = (0..players).collect();
+        by_size.sort_by_key(|&player| std::cmp::Reverse(contributions[player]));
+        winnings[by_size[0]] = winnings[by_size[0]].saturating_sub(contributions[by_size[0]] - contributions[by_size[1]]);
+        let winners = (0..players).filter(|&player| winnings[player] > 0).collect();
+        let mut recorded_winners: Vec<usize> = self.collected.iter().map(|&(player, _)| player).collect();
+        recorded_winners.sort_unstable();
+        recorded_winners.dedup();
+        Ok(Replay { streets, hands, grades, winnings, winners, recorded_winners })
+    }
+}
+
+#[cfg(test)]
+mod test_for_history {
+    use crate::agent::*;
+    use crate::arena::play_hand;
+    use crate::betting::*;
+    use crate::deck::*;
+    use crate::grade::Grade;
+    use crate::history::*;
+    use crate::holdem::HoldemGame;
+
+    fn grade(cards: &str) -> Grade {
+        Grade::from_set(parse_cards(cards).unwrap().iter().collect())
+    }
+
+    #[test]
+    fn grade_descriptions() {
+        assert_eq!(describe_grade(&grade("As Ks Qs Js Ts 2d 3c")), "a Royal Flush");
+        assert_eq!(describe_grade(&grade("9h Th Jh Qh Kh 2d 3c")), "a straight flush, Nine to King");
+        assert_eq!(describe_grade(&grade("Ah 2d 3c 4s 5h 9d Kc")), "a straight, Ace to Five");
+        assert_eq!(describe_grade(&grade("7h 7d 7c Ks Kh 2d 3c")), "a full house, Sevens full of Kings");
+        assert_eq!(describe_grade(&grade("Kh Kd 9c 9s 2h 3d 4c")), "two pair, Kings and Nines");
+        assert_eq!(describe_grade(&grade("6h 6d 9c Js 2h 3d 4c")), "a pair of Sixes");
+        assert_eq!(describe_grade(&grade("Ah Kh 2h 5h 9h")), "a flush, Ace high");
+        assert_eq!(describe_grade(&grade("Ah 2d 7c 9s Jh")), "high card Ace");
+    }
+
+    #[test]
+    fn showdown_hand() {
+        let mut game = HoldemGame::with_seed(3, 4);
+        game.set_hole_cards(0, parse_cards("Ah Kd").unwrap()).unwrap();
+        game.set_hole_cards(2, parse_cards("Qs Qh").unwrap()).unwrap();
+        let mut table = HoldemTable::new(game, vec![200, 150, 100], 0, Blinds::new(1, 2)).unwrap();
+        // three-handed, the button acts first.
+        table.act(Action::Raise(6)).unwrap();
+        table.act(Action::Fold).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Check).unwrap();
+        table.act(Action::Bet(8)).unwrap();
+        table.act(Action::Raise(94)).unwrap();
+        table.act(Action::Call).unwrap();
+        let info = HandInfo::new(42).with_names(vec![String::from("alice"), String::from("bob")]);
+        let text = export_pokerstars(&table, &info).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "PokerStars Hand #42:  Hold'em No Limit (1/2) - 1970/01/01 0:00:00 ET");
+        assert_eq!(lines[1], "Table 'poker_env' 3-max Seat #1 is the button");
+        assert_eq!(lines[2..5], ["Seat 1: alice (200 in chips)", "Seat 2: bob (150 in chips)", "Seat 3: Player3 (100 in chips)"]);
+        assert_eq!(lines[5..8], ["bob: posts small blind 1", "Player3: posts big blind 2", "*** HOLE CARDS ***"]);
+        assert!(text.contains("Dealt to alice [Ah Kd]\n"));
+        assert!(text.contains("Dealt to Player3 [Qs Qh]\n"));
+        assert!(text.contains("alice: raises 4 to 6\nbob: folds\nPlayer3: calls 4\n*** FLOP *** ["));
+        assert!(text.contains("Player3: checks\nalice: bets 8\nPlayer3: raises 86 to 94 and is all-in\nalice: calls 86\n*** TURN *** ["));
+        assert!(text.contains("*** SHOW DOWN ***\n"));
+        assert!(text.contains("Total pot 201 | Rake 0\n"));
+        assert!(text.contains("Seat 2: bob (small blind) folded before Flop\n"));
+        let board = table.game().board_cards();
+        assert!(text.contains(&format!("Board [{}]\n", board.iter().map(|card| card.to_text()).collect::<Vec<_>>().join(" "))));
+        let winner = if table.winnings()[0] > 0 { "alice" } else { "Player3" };
+        assert!(text.contains(&format!("{} collected 201 from pot\n", winner)));
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn uncalled_bet_and_side_pots() {
+        let mut table = HoldemTable::new(HoldemGame::with_seed(3, 8), vec![50, 100, 300], 2, Blinds::new(1, 2)).unwrap();
+        // seat 0 is the small blind, seat 1 the big blind and seat 2 the button.
+        table.act(Action::Raise(300)).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Call).unwrap();
+        let text = export_pokerstars(&table, &HandInfo::new(1).with_hero(2)).unwrap();
+        assert!(text.contains("Player3: raises 298 to 300 and is all-in\nPlayer1: calls 49 and is all-in\nPlayer2: calls 98 and is all-in\n"));
+        assert!(text.contains("Uncalled bet (200) returned to Player3\n*** FLOP ***"));
+        assert!(text.contains("Total pot 250 Main pot 150. Side pot 100. | Rake 0\n"));
+        assert_eq!(text.matches("Dealt to").count(), 1);
+        assert!(text.contains("Seat 3: Player3 (button) "));
+        assert!(text.contains("Seat 1: Player1 (small blind) showed ["));
+        // a hand won without a showdown.
+        let mut table = HoldemTable::new(HoldemGame::with_seed(2, 8), vec![100, 100], 0, Blinds::new(1, 2)).unwrap();
+        table.act(Action::Raise(10)).unwrap();
+        table.act(Action::Fold).unwrap();
+        let text = export_pokerstars(&table, &HandInfo::new(2)).unwrap();
+        assert!(text.contains("Uncalled bet (8) returned to Player1\nPlayer1 collected 4 from pot\nPlayer1: doesn't show hand\n"));
+        assert!(text.contains("Seat 1: Player1 (button) (small blind) collected (4)\n"));
+        assert!(text.contains("Seat 2: Player2 (big blind) folded before Flop\n"));
+        assert!(!text.contains("Board"));
+        let unfinished = HoldemTable::new(HoldemGame::with_seed(2, 8), vec![100, 100], 0, Blinds::new(1, 2)).unwrap();
+        assert!(export_pokerstars(&unfinished, &HandInfo::new(3)).is_err());
+    }
+
+    #[test]
+    fn exported_hands_parse_and_replay() {
+        for seed in 0..60 {
+            let players = 2 + seed as usize % 5;
+            let stacks = (0..players).map(|seat| 40 + 30 * seat as u64).collect();
+            let blinds = Blinds::new(1, 2).with_ante(seed % 2);
+            let mut table = HoldemTable::new(HoldemGame::with_seed(players, seed), stacks, seed as usize % players, blinds).unwrap();
+            let mut agents: Vec<Box<dyn Agent>> = (0..players).map(|seat| Box::new(RandomAgent::new(seed * 10 + seat as u64)) as Box<dyn Agent>).collect();
+            play_hand(&mut table, &mut agents).unwrap();
+            let text = export_pokerstars(&table, &HandInfo::new(seed)).unwrap();
+            let history: HandHistory = text.parse().unwrap();
+            assert_eq!((history.hand_id, history.small_blind, history.big_blind), (seed, 1, 2));
+            assert_eq!(history.structure, "No Limit");
+            assert_eq!(history.button, Some(table.button()));
+            assert_eq!(history.actions, table.history());
+            assert_eq!(history.players.iter().map(|player| player.stack).collect::<Vec<u64>>(), table.starting_stacks());
+            let replay = history.replay().unwrap();
+            assert!(replay.winners_match(), "{}", text);
+            let streets = replay.streets.last().unwrap();
+            assert_eq!(streets.board, history.board);
+            assert_eq!(streets.pot, table.contributions().iter().sum::<u64>());
+            let mut collected = vec![0; players];
+            for &(player, amount) in &history.collected {
+                collected[player] += amount;
+            }
+            assert_eq!(replay.winnings, collected);
+        }
+    }
+
+    #[test]
+    fn parses_a_cash_game_file() {
+        let text = "\u{feff}PokerStars Hand #250000000001:  Hold'em No Limit ($0.05/$0.10 USD) - 2024/03/01 20:15:11 CET [2024/03/01 14:15:11 ET]
+Table 'Alcyone IV' 6-max Seat #4 is the button
+Seat 1: big fish ($10.40 in chips)
+Seat 2: shark99 ($9.85 in chips) is sitting out
+Seat 4: hero ($10 in chips)
+Seat 6: big ($12.05 in chips)
+big: posts small blind $0.05
+big fish: posts big blind $0.10
+*** HOLE CARDS ***
+Dealt to hero [Ac Kc]
+hero: raises $0.20 to $0.30
+big: calls $0.25
+big fish: folds
+*** FLOP *** [Kh 7d 2c]
+big: checks
+hero: bets $0.45
+big: calls $0.45
+*** TURN *** [Kh 7d 2c] [9s]
+big: checks
+big fish said, \"nice hand\"
+hero: checks
+*** RIVER *** [Kh 7d 2c 9s] [7c]
+big: bets $1
+hero: calls $1
+*** SHOW DOWN ***
+big: shows [9h 9d] (a full house, Nines full of Sevens)
+hero: mucks hand
+big collected $3.41 from pot
+*** SUMMARY ***
+Total pot $3.60 | Rake $0.19
+Board [Kh 7d 2c 9s 7c]
+Seat 6: big (small blind) showed [9h 9d] and won ($3.41)
+
+
+PokerStars Hand #250000000002:  Hold'em Pot Limit ($0.05/$0.10 USD) - 2024/03/01 20:16:02 CET [2024/03/01 14:16:02 ET]
+Table 'Alcyone IV' 6-max Seat #6 is the button
+Seat 1: big fish ($10.30 in chips)
+Seat 6: big ($13.66 in chips)
+big: posts small blind $0.05
+big fish: posts big blind $0.10
+*** HOLE CARDS ***
+big: raises $0.20 to $0.30
+big fish: folds
+Uncalled bet ($0.20) returned to big
+big collected $0.20 from pot
+big: doesn't show hand
+*** SUMMARY ***
+";
+        let histories = parse_histories(text).unwrap();
+        assert_eq!(histories.len(), 2);
+        let first = &histories[0];
+        assert_eq!((first.hand_id, first.small_blind, first.big_blind), (250000000001, 5, 10));
+        assert_eq!(first.table_name, "Alcyone IV");
+        assert_eq!((first.max_seats, first.button), (6, Some(2)));
+        assert_eq!(first.date, "2024/03/01 20:15:11 CET [2024/03/01 14:15:11 ET]");
+        let names: Vec<&str> = first.players.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, ["big fish", "shark99", "hero", "big"]);
+        assert_eq!(first.players[0].stack, 1040);
+        assert_eq!(first.hole_cards[2], Some(parse_cards("Ac Kc").unwrap()));
+        assert_eq!(first.hole_cards[3], Some(parse_cards("9h 9d").unwrap()));
+        assert_eq!(first.actions[0], ActionRecord { stage: Stage::PreFlop, seat: 2, action: Action::Raise(30), amount: 30 });
+        assert_eq!(first.actions[1], ActionRecord { stage: Stage::PreFlop, seat: 3, action: Action::Call, amount: 25 });
+        assert_eq!(first.board, parse_cards("Kh 7d 2c 9s 7c").unwrap());
+        assert!(first.showdown);
+        assert_eq!(first.collected, vec![(3, 341)]);
+        let replay = first.replay().unwrap();
+        assert_eq!(replay.streets.len(), 4);
+        assert_eq!(replay.streets.iter().map(|street| street.pot).collect::<Vec<u64>>(), [70, 160, 160, 360]);
+        assert_eq!(replay.winnings, [0, 0, 0, 360]);
+        assert!(replay.winners_match());
+        assert_eq!(describe_grade(replay.grades[3].as_ref().unwrap()), "a full house, Nines full of Sevens");
+        // with no hand known at the showdown there is nothing to check.
+        let mut mucked = first.clone();
+        mucked.hole_cards[2] = None;
+        mucked.hole_cards[3] = None;
+        assert!(mucked.replay().is_err());
+        let second = &histories[1];
+        assert_eq!(second.structure, "Pot Limit");
+        assert_eq!(second.uncalled, Some((1, 20)));
+        assert_eq!(second.replay().unwrap().winnings, [0, 20]);
+        assert_eq!(parse_histories("nothing here"), Err(ParseHistoryError::Empty));
+        assert!("PokerStars Hand #x: Hold'em No Limit (1/2)".parse::<HandHistory>().is_err());
+    }
+
+    #[test]
+    fn parses_zoom_and_tournament_hands() {
+        let zoom = "PokerStars Zoom Hand #250000000010:  Hold'em No Limit ($0.05/$0.10) - 2024/03/02 10:00:00 ET
+Table 'Donati' 6-max Seat #1 is the button
+Seat 1: u1 ($10 in chips)
+Seat 2: u2 ($10 in chips)
+Seat 3: u3 ($10 in chips)
+Seat 4: u4 ($10 in chips)
+u2: posts small blind $0.05
+u3: posts big blind $0.10
+u4: posts straddle $0.20
+u1: posts small & big blinds $0.15
+*** HOLE CARDS ***
+u1: raises $0.50 to $0.60
+u2: folds
+u3: folds
+u4: calls $0.40
+*** FLOP *** [Ah 8d 3s]
+u4: checks
+u1: checks
+*** TURN *** [Ah 8d 3s] [2c]
+u4: bets $1
+u1: calls $1
+*** RIVER *** [Ah 8d 3s 2c] [Kd]
+u4: checks
+u1: checks
+*** SHOW DOWN ***
+u1: shows [As Ks] (two pair, Aces and Kings)
+u4: shows [8s 8c] (three of a kind, Eights)
+u4 collected $3.35 from pot
+*** SUMMARY ***
+Total pot $3.40 | Rake $0.05
+";
+        let tournament_text = "PokerStars Hand #249000000003: Tournament #3500000000, $1.00+$0.10 USD Hold'em No Limit - Level II (15/30) - 2024/03/01 21:00:00 ET
+Table '3500000000 1' 9-max Seat #1 is the button
+Seat 1: alpha (1500 in chips)
+Seat 4: beta (1470 in chips)
+Seat 7: gamma (1530 in chips) is sitting out
+alpha: posts the ante 5
+beta: posts the ante 5
+gamma: posts the ante 5
+beta: posts small blind 15
+gamma: posts big blind 30
+*** HOLE CARDS ***
+Dealt to alpha [Qd Qc]
+gamma: is sitting out
+alpha: raises 60 to 90
+beta: calls 75
+gamma: folds
+*** FLOP *** [Qs 8h 3c]
+beta: checks
+alpha: bets 120
+beta: folds
+Uncalled bet (120) returned to alpha
+alpha collected 225 from pot
+alpha: doesn't show hand
+*** SUMMARY ***
+Total pot 225 | Rake 0
+";
+        let histories = parse_histories(&format!("{}\n\n{}", zoom, tournament_text)).unwrap();
+        assert_eq!(histories.len(), 2);
+        let zoom = &histories[0];
+        assert_eq!((zoom.hand_id, zoom.small_blind, zoom.big_blind), (250000000010, 5, 10));
+        assert_eq!(zoom.date, "2024/03/02 10:00:00 ET");
+        assert_eq!(zoom.posts[2], Post { player: 3, kind: PostKind::Straddle, amount: 20 });
+        assert_eq!(zoom.posts[3], Post { player: 0, kind: PostKind::SmallAndBigBlind, amount: 15 });
+        // only the big blind of a dead blind post plays, so the raise puts in 50 more.
+        assert_eq!(zoom.actions[0], ActionRecord { stage: Stage::PreFlop, seat: 0, action: Action::Raise(60), amount: 50 });
+        assert_eq!(zoom.actions[3], ActionRecord { stage: Stage::PreFlop, seat: 3, action: Action::Call, amount: 40 });
+        let replay = zoom.replay().unwrap();
+        assert_eq!(replay.streets.last().unwrap().pot, 340);
+        assert_eq!(replay.winnings, [0, 0, 0, 335]);
+        assert!(replay.winners_match());
+
+        let tournament = &histories[1];
+        assert_eq!((tournament.hand_id, tournament.small_blind, tournament.big_blind), (249000000003, 15, 30));
+        assert_eq!(tournament.structure, "No Limit");
+        assert_eq!(tournament.date, "2024/03/01 21:00:00 ET");
+        assert_eq!(tournament.table_name, "3500000000 1");
+        assert_eq!((tournament.max_seats, tournament.button), (9, Some(0)));
+        assert_eq!(tournament.posts.iter().filter(|post| post.kind == PostKind::Ante).count(), 3);
+        assert_eq!(tournament.actions[1], ActionRecord { stage: Stage::PreFlop, seat: 1, action: Action::Call, amount: 75 });
+        assert_eq!(tournament.uncalled, Some((0, 120)));
+        let replay = tournament.replay().unwrap();
+        assert_eq!(replay.winnings, [225, 0, 0]);
+        assert!(replay.winners_match());
+
+        // a line the parser does not know is an error, not a silent skip.
+        let unknown = tournament_text.replace("beta: calls 75", "beta: posts a bounty 75");
+        assert_eq!(unknown.parse::<HandHistory>(), Err(ParseHistoryError::InvalidLine(String::from("beta: posts a bounty 75"))));
+    }
+
+    #[test]
+    fn dead_button_and_unknown_lines() {
+        let text = "PokerStars Hand #249000000004: Tournament #3500000000, $1.00+$0.10 USD Hold'em No Limit - Level I (10/20) - 2024/03/01 21:05:00 ET
+Table '3500000000 1' 9-max Seat #3 is the button
+Seat 1: alpha (1500 in chips)
+Seat 4: beta (1500 in chips)
+Seat 7: gamma (1500 in chips)
+alpha: posts the ante 5
+beta: posts the ante 5
+gamma: posts the ante 5
+beta: posts small blind 10
+gamma: posts big blind 20
+delta joins the table at seat #9
+*** HOLE CARDS ***
+alpha: raises 20 to 40
+beta: folds
+gamma: calls 20
+alpha said, \"gl\"
+*** FLOP *** [As Ks Qs]
+gamma is disconnected
+gamma is connected
+gamma: checks
+alpha: checks
+*** TURN *** [As Ks Qs] [Js]
+gamma: checks
+alpha: checks
+*** RIVER *** [As Ks Qs Js] [Ts]
+gamma: checks
+alpha: checks
+*** SHOW DOWN ***
+gamma: shows [2d 3c] (a straight flush, Ace high)
+alpha: shows [2c 3d] (a straight flush, Ace high)
+gamma collected 53 from pot
+alpha collected 52 from pot
+beta leaves the table
+*** SUMMARY ***
+Total pot 105 | Rake 0
+";
+        let history: HandHistory = text.parse().unwrap();
+        assert_eq!((history.button, history.button_seat), (None, 3));
+        assert_eq!(history.actions.len(), 9);
+        // seat 3 is empty : gamma, the first player left of it after beta folded, gets the odd chip.
+        let replay = history.replay().unwrap();
+        assert_eq!(replay.winnings, [52, 0, 53]);
+        assert!(replay.winners_match());
+
+        // lines the parser does not know are errors, not silent skips.
+        for line in ["ghost: raises 50 to 100", "Hand was cancelled"] {
+            let unknown = text.replace("beta: folds", &format!("beta: folds\n{}", line));
+            assert_eq!(unknown.parse::<HandHistory>(), Err(ParseHistoryError::InvalidLine(String::from(line))));
+        }
+    }
+}
@@ -1,81 +1,146 @@
 use std::fmt::Debug;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use crate::cardset::CardSet;
 use crate::deck::*;
+use crate::equity::*;
 use crate::hand::*;
-use crate::grade::*;
+use crate::evaluator::*;
+use crate::range::*;
 
-#[derive(Debug, Clone, Copy)]
-enum Stage {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
 	PreFlop,
 	Flop,
 	Turn,
 	River,
 }
 
-enum Showdown {
-	Win,
-	Lose,
-	Draw,
-}
-
+// the rng is the chacha12 generator behind StdRng, named so that its state can be saved.
 #[derive(Debug)]
 pub struct HoldemGame {
-	deck: Deck,
-	stage: Stage,
-	community_cards: Vec<Card>,
-	players_cnt: usize,
-	players: Vec<Hand>,
+	pub(crate) deck: Deck,
+	pub(crate) stage: Stage,
+	pub(crate) community_cards: Vec<Card>,
+	pub(crate) players_cnt: usize,
+	pub(crate) players: Vec<Hand>,
+	pub(crate) dead_cards: CardSet,
+	pub(crate) ranges: Vec<Option<Range>>,
+	pub(crate) rng: ChaCha12Rng,
 }
 
 impl Clone for HoldemGame {
 	fn clone(&self) -> Self {
-		let deck : Deck = self.deck.clone();
+		let deck : Deck = self.deck;
 		let stage : Stage = self.stage;
 		let community_cards : Vec<Card> = self.community_cards.clone();
 		let players_cnt: usize = self.players_cnt;
 		let players: Vec<Hand> = self.players.clone();
-		Self {deck, stage, community_cards, players_cnt, players}
+		let dead_cards: CardSet = self.dead_cards;
+		let ranges: Vec<Option<Range>> = self.ranges.clone();
+		let rng: ChaCha12Rng = self.rng.clone();
+		Self {deck, stage, community_cards, players_cnt, players, dead_cards, ranges, rng}
 	}
 }
 
 impl HoldemGame {
 	pub fn new(players_cnt: usize) -> Self {
+		Self::with_chacha_rng(players_cnt, ChaCha12Rng::from_entropy())
+	}
+
+	// the same seed deals the same cards and gives the same win rate.
+	pub fn with_seed(players_cnt: usize, seed: u64) -> Self {
+		Self::with_chacha_rng(players_cnt, ChaCha12Rng::seed_from_u64(seed))
+	}
+
+	pub fn with_rng<R: Rng + ?Sized>(players_cnt: usize, rng: &mut R) -> Self {
+		let rng = ChaCha12Rng::from_rng(rng).expect("failed to seed the game's rng.");
+		Self::with_chacha_rng(players_cnt, rng)
+	}
+
+	fn with_chacha_rng(players_cnt: usize, rng: ChaCha12Rng) -> Self {
 		if players_cnt > 10 {panic!("Too many players on this game.");}
 		let deck = Deck::new();
 		let mut players = Vec::<Hand>::new();
 		for _ in 0..players_cnt {players.push(Hand::new());}
 		
-		Self {	deck: deck,
+		Self {	deck,
 			stage: Stage::PreFlop,
 			community_cards: Vec::<Card>::new(),
-			players_cnt: players_cnt,
-			players: players
+			players_cnt,
+			players,
+			dead_cards: CardSet::EMPTY,
+			ranges: vec![None; players_cnt],
+			rng
 		}
 	}
 	
 	pub fn add_community_card(&mut self) -> Result<(), &str> {
+		if self.community_cards.len() >= 5 {return Err("Community Cards cannot be over 5 cards.");}
 		let dealt_card : Card = self.deck.deal_cards(1)[0];
-		if self.community_cards.len() < 5 {
-			for i in 0..self.players_cnt {
-				self.players[i].add_card(dealt_card);
-			}
-			self.community_cards.push(dealt_card);
+		self.push_community_card(dealt_card);
+		Ok(())
+	}
+
+	fn push_community_card(&mut self, card: Card) {
+		for i in 0..self.players_cnt {
+			self.players[i].add_card(card);
 		}
-		else {return Err("Community Cards cannot be over 5 cards.");}
-		return Ok(());
+		self.community_cards.push(card);
+		self.stage = match self.community_cards.len() {
+			0..=2 => Stage::PreFlop,
+			3 => Stage::Flop,
+			4 => Stage::Turn,
+			_ => Stage::River,
+		};
 	}
 
-	fn preflop(&mut self) -> () {
+	// takes the given cards out of the deck, failing if one is repeated or already dealt.
+	fn deal_known_cards(&mut self, cards: &[Card]) -> Result<(), &'static str> {
+		let known: CardSet = cards.iter().collect();
+		if known.len() != cards.len() {return Err("Known cards contain a duplicate.");}
+		self.deck.deal_known_cards(known)?;
+		Ok(())
+	}
+
+	// opens the given cards on the board, so that the hand goes on from the flop, turn or river.
+	pub fn set_board_cards(&mut self, board_cards: Vec<Card>) -> Result<(), &str> {
+		let board_len = self.community_cards.len() + board_cards.len();
+		if board_len > 5 {return Err("Community Cards cannot be over 5 cards.");}
+		if board_len < 3 {return Err("Board Cards must open at least the flop.");}
+		self.deal_known_cards(&board_cards)?;
+		board_cards.into_iter().for_each(|card| self.push_community_card(card));
+		Ok(())
+	}
+
+	// dead cards are out of play : they are never dealt nor part of any runout.
+	pub fn set_dead_cards(&mut self, dead_cards: Vec<Card>) -> Result<(), &str> {
+		self.deal_known_cards(&dead_cards)?;
+		self.dead_cards.extend(dead_cards);
+		Ok(())
+	}
+
+	pub fn dead_cards(&self) -> CardSet {
+		self.dead_cards
+	}
+
+	pub fn board_cards(&self) -> &[Card] {
+		&self.community_cards
+	}
+
+	pub fn preflop(&mut self) {
+		let hand_len = 2 + self.community_cards.len();
 		self.players.iter_mut().for_each(
 			|player|
-			while player.len() < 2 { player.add_card(self.deck.deal_cards(1)[0]) }
+			while player.len() < hand_len { player.add_card(self.deck.deal_cards(1)[0]) }
 		);
 	}
 
-	fn preflop_to_flop(&mut self) -> () {
+	pub fn preflop_to_flop(&mut self) {
 	// preflop is the state in which every player gets 2 cards.
 	// flop is the state in which 3 community cards are open.
 		if let Stage::PreFlop = self.stage {
-			for _ in 0..3 { 
+			while self.community_cards.len() < 3 {
 				if let Err(msg) = self.add_community_card() {panic!("{}", msg);}
 			}
 			self.stage = Stage::Flop;
@@ -84,7 +149,7 @@ impl HoldemGame {
 		}
 	}
 	
-	fn flop_to_turn(&mut self) -> () {
+	pub fn flop_to_turn(&mut self) {
 		if let Stage::Flop = self.stage {
 			if let Err(msg) = self.add_community_card() {panic!("{}", msg);}
 			self.stage = Stage::Turn;
@@ -93,7 +158,7 @@ impl HoldemGame {
 		}
 	}
 
-	fn turn_to_river(&mut self) -> () {
+	pub fn turn_to_river(&mut self) {
 		if let Stage::Turn = self.stage {
 			if let Err(msg) = self.add_community_card() {panic!("{}", msg);}
 			self.stage = Stage::River;
@@ -102,64 +167,161 @@ impl HoldemGame {
 		}
 	}
 	
-	fn play_once(&mut self) -> Showdown {
-		self.deck.partial_shuffle();
-		// println!("{:?}", self.deck);
-		self.preflop();
-		//println!("{:?}", self);
+	// opens the community cards of the next stage.
+	pub fn next_stage(&mut self) {
 		match self.stage {
-			Stage::PreFlop => {
-				self.preflop_to_flop();
-				self.flop_to_turn();
-				self.turn_to_river();
-			},
-			Stage::Flop => {
-				self.flop_to_turn();
-				self.turn_to_river();
-			},
-			Stage::Turn => {
-				self.turn_to_river();
+			Stage::PreFlop => self.preflop_to_flop(),
+			Stage::Flop => self.flop_to_turn(),
+			Stage::Turn => self.turn_to_river(),
+			Stage::River => panic!("{}", "next_stage function cannot be called at river stage."),
+		}
+	}
+
+	// shuffles the undealt cards with the game's own rng and deals every player's hole cards.
+	pub fn deal_hole_cards(&mut self) {
+		self.deck.partial_shuffle_with(&mut self.rng);
+		self.preflop();
+	}
+
+	// deals the rest of the hand from the game's own deck and rng and ranks every player.
+	pub fn play_once(&mut self) -> Vec<HandRank> {
+		self.deal_hole_cards();
+		while self.stage != Stage::River {
+			self.next_stage();
+		}
+		self.players.iter().map(|player| evaluate(player.card_set())).collect()
+	}
+
+	pub fn stage(&self) -> Stage {
+		self.stage
+	}
+
+	pub fn players_cnt(&self) -> usize {
+		self.players_cnt
+	}
+
+	// every player's hand : hole cards and board.
+	pub fn players(&self) -> &[Hand] {
+		&self.players
+	}
+
+	pub fn hole_cards(&self, seat: usize) -> CardSet {
+		self.players[seat].card_set() - self.community_cards.iter().collect()
+	}
+
+	// seats holding a range are dealt from it, as in calc_range_equity.
+	pub fn calc_win_rate(&self) -> Result<f32, &str> {
+		let mode = EquityMode::MonteCarlo(MonteCarloConfig::default());
+		match self.ranges.iter().any(Option::is_some) {
+			true => Ok(self.calc_range_equity(mode)?.win as f32),
+			false => Ok(self.calc_equity(mode)?.win() as f32),
+		}
+	}
+
+	// equity of players[0] against the others, on every available core.
+	pub fn calc_equity(&self, mode: EquityMode) -> Result<Equity, &str> {
+		Ok(self.calc_equities(mode)?.swap_remove(0))
+	}
+
+	pub fn calc_equity_with_threads(&self, mode: EquityMode, threads: usize) -> Result<Equity, &str> {
+		Ok(self.calc_equities_with_threads(mode, threads)?.swap_remove(0))
+	}
+
+	// equity of every player, in seat order. split pots are shared evenly among the winners.
+	pub fn calc_equities(&self, mode: EquityMode) -> Result<Vec<Equity>, &str> {
+		let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+		self.calc_equities_with_threads(mode, threads)
+	}
+
+	// the result is the same for any number of threads, unless a time limit stops the simulation.
+	// seats holding a range need calc_range_equity.
+	pub fn calc_equities_with_threads(&self, mode: EquityMode, threads: usize) -> Result<Vec<Equity>, &str> {
+		if self.ranges.iter().any(Option::is_some) {return Err("Seats holding a range need calc_range_equity.");}
+		let spot = self.spot();
+		if !spot.has_cards_left() {return Err("Too few cards left for every seat and the board.");}
+		let (tallies, exact) = match mode.montecarlo_config(spot.exact_showdowns()) {
+			None => (spot.enumerate(threads), true),
+			Some(config) => {
+				// every call replays the game's own rng, so the estimate is reproducible.
+				let seed: u64 = self.rng.clone().gen();
+				(spot.simulate(&config, seed, threads), false)
 			},
-			Stage::River => {},
 		};
-		//println!("{:?}", self);
-		let my_grade: Grade = Grade::new(&self.players[0].cards);
-		let other_grades : Vec<Grade> = self.players.iter()
-						.skip(1).map(|player| Grade::new(&player.cards) )
-						.collect::<Vec<_>>();
-		//println!("{:?}", my_grade);
-		//println!("{:?}", other_grades[0]);
-		let mut draw_cnt : u8 = 0;
-		let mut win_cnt : u8 = 0;
-		let mut lose_cnt : u8 = 0;
-		other_grades.into_iter().for_each(
-			|og| 
-			if 	my_grade == og 	{draw_cnt += 1} 
-			else if my_grade > og 	{win_cnt += 1} 
-			else 			{lose_cnt += 1});
-		if lose_cnt > 0 	{Showdown::Lose}
-		else if draw_cnt > 0 	{Showdown::Draw}
-		else			{Showdown::Win}
-	}
-
-	pub fn calc_win_rate(&self) -> f32 {
-		let mut win_cnt : usize = 0;
-		let mut lose_cnt :usize = 0;
-		let mut draw_cnt :usize = 0;
-		for _ in 0..50000 {
-			let mut cloned_self = self.clone();
-			match cloned_self.play_once() {
-				Showdown::Win => {win_cnt += 1;},
-				Showdown::Lose => {lose_cnt += 1;},
-				Showdown::Draw => {draw_cnt += 1;}
+		Ok(tallies.into_iter().map(|tally| tally.to_equity(exact)).collect())
+	}
+
+	// number of showdowns EquityMode::Exact would go through.
+	pub fn exact_showdowns(&self) -> u128 {
+		self.spot().exact_showdowns()
+	}
+
+	fn spot(&self) -> Spot {
+		let board: CardSet = self.community_cards.iter().collect();
+		let holes = self.players.iter().map(|player| player.card_set() - board).collect();
+		Spot {board, dead: self.dead_cards, holes}
+	}
+
+	// equity of players[0]'s hand or range against the other seats' ranges, taking out the
+	// combos that share a card with a known one. seats without hand nor range hold any combo.
+	pub fn calc_range_equity(&self, mode: EquityMode) -> Result<RangeEquity, &str> {
+		let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+		self.calc_range_equity_with_threads(mode, threads)
+	}
+
+	pub fn calc_range_equity_with_threads(&self, mode: EquityMode, threads: usize) -> Result<RangeEquity, &str> {
+		let spot = self.range_spot()?;
+		Ok(match mode.montecarlo_config(spot.exact_showdowns()) {
+			None => spot.enumerate(threads),
+			Some(config) => spot.simulate(&config, self.rng.clone().gen(), threads),
+		})
+	}
+
+	fn range_spot(&self) -> Result<RangeSpot, &'static str> {
+		let spot = self.spot();
+		// checked first : with too few cards, looking for a deal tries every combo of every seat.
+		if !spot.has_cards_left() {return Err("Too few cards left for every seat and the board.");}
+		let Spot {board, dead, holes} = spot;
+		let known = holes.iter().fold(board | dead, |known, &hole| known | hole);
+		let mut seats = Vec::with_capacity(self.players_cnt);
+		for (hole, range) in holes.iter().zip(&self.ranges) {
+			let cards = hole.to_vec();
+			let combos: Vec<(Combo, f64)> = match cards[..] {
+				[a, b] => Combo::new(a, b).map(|combo| (combo, 1.0)).into_iter().collect(),
+				_ => {
+					let range = range.clone().unwrap_or_else(Range::full);
+					range.without_cards(known - *hole).combos().filter(|(combo, _)| hole.is_subset(combo.card_set())).collect()
+				},
 			};
+			if combos.is_empty() {return Err("No combo left in a range.");}
+			seats.push(combos);
 		}
-		return win_cnt as f32 / (win_cnt as f32 + lose_cnt as f32 + draw_cnt as f32);
+		let spot = RangeSpot {board, dead, seats};
+		if !spot.has_deal() {return Err("Ranges leave no possible deal.");}
+		Ok(spot)
+	}
+
+	// the seat is dealt from the range in range equity calculations.
+	pub fn set_range(&mut self, seat: usize, range: Range) -> Result<(), &str> {
+		if seat >= self.players_cnt {return Err("No such seat on this game.");}
+		if self.players[seat].len() > self.community_cards.len() {return Err("Front Cards' Slot Already occupied.");}
+		if range.is_empty() {return Err("Range has no combo.");}
+		self.ranges[seat] = Some(range);
+		Ok(())
 	}
 
 	pub fn set_front_cards(&mut self, front_cards: Vec<Card>) -> Result<(), &str> {
-		self.players[0].add_cards(&front_cards);
-		self.deck.set_front_cards(front_cards)
+		self.set_hole_cards(0, front_cards)
+	}
+
+	// gives a seat known hole cards. seats left without them get random ones.
+	pub fn set_hole_cards(&mut self, seat: usize, hole_cards: Vec<Card>) -> Result<(), &str> {
+		if seat >= self.players_cnt {return Err("No such seat on this game.");}
+		if self.players[seat].len() > self.community_cards.len() {return Err("Front Cards' Slot Already occupied.");}
+		if self.ranges[seat].is_some() {return Err("The seat already holds a range.");}
+		if hole_cards.len() > 2 {return Err("Front Cards cannot be over 2 cards.");}
+		self.deal_known_cards(&hole_cards)?;
+		self.players[seat].add_cards(&hole_cards);
+		Ok(())
 	}
 }
 
@@ -169,14 +331,264 @@ mod montecarlo_test {
     use crate::holdem::*;
     #[test]
     fn motecarlo_test_00() {	
-	let mut game = HoldemGame::new(3);
-	let frontcards = vec![Card{shape:0, num:12}, Card{shape:1, num:12}];
-	game.set_front_cards(frontcards);
-	println!("{:?}{:?}", game.deck.cards[0], game.deck.cards[1]);
-	let win_rate = game.calc_win_rate();
-	println!("{}", win_rate);
-	// assert!(0.8236-0.01 < win_rate && win_rate < 0.8236+0.01 );
-	assert!(0.735-0.01 < win_rate && win_rate < 0.735+0.01 );
+	let mut game = HoldemGame::with_seed(3, 20220601);
+	let frontcards = vec![Card::new(Rank::Ace, Suit::Spade), Card::new(Rank::Ace, Suit::Diamond)];
+	game.set_front_cards(frontcards).unwrap();
+	// the seed fixes every runout, so the estimate is exact. aces win about 73.5% three-handed.
+	assert_eq!(game.calc_win_rate().unwrap(), 0.73284);
+    }
+
+    #[test]
+    fn same_seed_gives_same_win_rate() {
+	let frontcards = parse_cards("Kh Qh").unwrap();
+	let mut game1 = HoldemGame::with_seed(2, 7);
+	let mut game2 = HoldemGame::with_seed(2, 7);
+	game1.set_front_cards(frontcards.clone()).unwrap();
+	game2.set_front_cards(frontcards).unwrap();
+	assert_eq!(game1.calc_win_rate().unwrap(), game2.calc_win_rate().unwrap());
+	assert_eq!(game1.calc_win_rate().unwrap(), game1.calc_win_rate().unwrap());
+    }
+
+    #[test]
+    fn exact_equity_on_the_river() {
+	let mut game = HoldemGame::new(2);
+	game.set_front_cards(parse_cards("As Ad").unwrap()).unwrap();
+	// the deck is not shuffled, so the board is a straight flush : 4s 5s 6s 7s 8s.
+	for _ in 0..5 {game.add_community_card().unwrap();}
+	assert_eq!(game.exact_showdowns(), 990);
+	let equity = game.calc_equity(EquityMode::Exact).unwrap();
+	assert!(equity.exact);
+	assert_eq!((equity.wins, equity.ties, equity.losses, equity.samples), (0, 946, 44, 990));
+	assert_eq!(equity.loss(), 44.0 / 990.0);
+	assert_eq!(equity.std_error, 0.0);
+	assert!((equity.equity - 946.0 / 990.0 / 2.0).abs() < 1e-12);
+	assert_eq!(game.calc_equity(EquityMode::default()).unwrap(), equity);
+    }
+
+    #[test]
+    fn auto_mode_falls_back_to_montecarlo() {
+	let mut game = HoldemGame::with_seed(2, 3);
+	game.set_front_cards(parse_cards("As Ad").unwrap()).unwrap();
+	assert_eq!(game.exact_showdowns(), 2_118_760 * 990);
+	let equity = game.calc_equity(EquityMode::default()).unwrap();
+	assert!(!equity.exact);
+	assert_eq!(equity.samples, DEFAULT_ITERATIONS as u64);
+	assert!(0.83 < equity.equity && equity.equity < 0.87);
+	assert_eq!(equity.wins + equity.ties + equity.losses, equity.samples);
+	let (low, high) = equity.confidence_interval(1.96);
+	assert!(low < 0.8520 && 0.8520 < high);
+    }
+
+    #[test]
+    fn montecarlo_stops_at_target_precision() {
+	let mut game = HoldemGame::with_seed(2, 3);
+	game.set_front_cards(parse_cards("Kh Qh").unwrap()).unwrap();
+	let config = MonteCarloConfig::new(1_000_000).with_target_std_error(0.01);
+	let equity = game.calc_equity(EquityMode::MonteCarlo(config)).unwrap();
+	assert!(equity.std_error <= 0.01);
+	assert!(equity.samples < 1_000_000);
+	assert_eq!(equity.samples % CHECK_INTERVAL as u64, 0);
+	assert_eq!(game.calc_equity(EquityMode::MonteCarlo(config)).unwrap(), equity);
+    }
+
+    #[test]
+    fn thread_count_does_not_change_equity() {
+	let mut game = HoldemGame::with_seed(3, 11);
+	game.set_front_cards(parse_cards("Js Ts").unwrap()).unwrap();
+	let mode = EquityMode::MonteCarlo(MonteCarloConfig::new(20000));
+	assert_eq!(game.calc_equity_with_threads(mode, 1).unwrap(), game.calc_equity_with_threads(mode, 4).unwrap());
+	let mut heads_up = HoldemGame::with_seed(2, 11);
+	heads_up.set_front_cards(parse_cards("Js Ts").unwrap()).unwrap();
+	for _ in 0..4 {heads_up.add_community_card().unwrap();}
+	assert_eq!(heads_up.calc_equity_with_threads(EquityMode::Exact, 1).unwrap(), heads_up.calc_equity_with_threads(EquityMode::Exact, 4).unwrap());
+    }
+
+    #[test]
+    fn known_board_and_dead_cards() {
+	let mut game = HoldemGame::with_seed(2, 9);
+	game.set_board_cards(parse_cards("Qh Jh Th 2c 3d").unwrap()).unwrap();
+	game.set_dead_cards(parse_cards("9h 8h").unwrap()).unwrap();
+	game.set_front_cards(parse_cards("Ah Kh").unwrap()).unwrap();
+	assert_eq!(game.board_cards(), &parse_cards("Qh Jh Th 2c 3d").unwrap()[..]);
+	assert_eq!(game.exact_showdowns(), 903);
+	let equity = game.calc_equity(EquityMode::default()).unwrap();
+	assert!(equity.exact);
+	assert_eq!((equity.wins, equity.ties, equity.losses), (903, 0, 0));
+	let ranks = game.play_once();
+	assert!(ranks[0] > ranks[1]);
+	assert_eq!(game.board_cards(), &parse_cards("Qh Jh Th 2c 3d").unwrap()[..]);
+	assert!(game.players[1].card_set().is_disjoint(game.dead_cards()));
+
+	let mut flop = HoldemGame::with_seed(2, 9);
+	flop.set_front_cards(parse_cards("Ah Kh").unwrap()).unwrap();
+	flop.set_board_cards(parse_cards("Qh Jh 2c").unwrap()).unwrap();
+	assert_eq!(flop.exact_showdowns(), 1081 * 990);
+	flop.set_board_cards(parse_cards("3d").unwrap()).unwrap();
+	assert_eq!(flop.exact_showdowns(), 46 * 990);
+    }
+
+    #[test]
+    fn duplicated_cards_are_rejected() {
+	let mut game = HoldemGame::with_seed(2, 9);
+	game.set_front_cards(parse_cards("Ah Kh").unwrap()).unwrap();
+	assert!(game.set_board_cards(parse_cards("Qh Jh").unwrap()).is_err());
+	assert!(game.set_board_cards(parse_cards("Qh Jh Ah").unwrap()).is_err());
+	assert!(game.set_board_cards(vec![Card::new(Rank::Two, Suit::Club); 3]).is_err());
+	assert!(game.set_dead_cards(parse_cards("Kh").unwrap()).is_err());
+	assert!(game.set_front_cards(parse_cards("2c 3c").unwrap()).is_err());
+	game.set_dead_cards(parse_cards("Qh").unwrap()).unwrap();
+	assert!(game.set_board_cards(parse_cards("Qh Jh Th").unwrap()).is_err());
+	assert!(game.board_cards().is_empty());
+	game.set_board_cards(parse_cards("Jh Th 9h 8h 7h").unwrap()).unwrap();
+	assert!(game.set_board_cards(parse_cards("2c").unwrap()).is_err());
+	assert!(game.add_community_card().is_err());
     }
 
+    #[test]
+    fn multiway_equity_with_known_hands() {
+	let mut game = HoldemGame::with_seed(3, 21);
+	game.set_hole_cards(0, parse_cards("As Ad").unwrap()).unwrap();
+	game.set_hole_cards(1, parse_cards("Kh Kc").unwrap()).unwrap();
+	game.set_hole_cards(2, parse_cards("Ac Kd").unwrap()).unwrap();
+	assert!(game.set_hole_cards(3, parse_cards("2c 2d").unwrap()).is_err());
+	assert!(game.set_hole_cards(1, parse_cards("2c").unwrap()).is_err());
+	assert!(game.set_board_cards(parse_cards("Kd 7c 2h").unwrap()).is_err());
+	game.set_board_cards(parse_cards("Ks 7c 2h").unwrap()).unwrap();
+	assert_eq!(game.exact_showdowns(), 903);
+	let equities = game.calc_equities(EquityMode::default()).unwrap();
+	assert_eq!(equities.len(), 3);
+	assert!(equities.iter().all(|equity| equity.exact && equity.samples == 903));
+	// seat 2 is drawing dead and no runout splits the pot.
+	assert_eq!(equities[2].equity, 0.0);
+	assert_eq!(equities[0].wins + equities[1].wins, 903);
+	assert!((equities.iter().map(|equity| equity.equity).sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn multiway_equity_splits_pots() {
+	let mut game = HoldemGame::with_seed(3, 21);
+	game.set_hole_cards(0, parse_cards("2c 3c").unwrap()).unwrap();
+	game.set_hole_cards(2, parse_cards("2d 3d").unwrap()).unwrap();
+	game.set_board_cards(parse_cards("As Ks Qh Jh Td").unwrap()).unwrap();
+	// the board plays for everyone : a three-way split every time.
+	let equities = game.calc_equities(EquityMode::Exact).unwrap();
+	assert_eq!(equities[1].samples, 903);
+	for equity in &equities {
+		assert_eq!(equity.tie(), 1.0);
+		assert!((equity.equity - 1.0 / 3.0).abs() < 1e-12);
+	}
+	let mut heads_up = HoldemGame::with_seed(2, 21);
+	heads_up.set_hole_cards(0, parse_cards("As Ad").unwrap()).unwrap();
+	heads_up.set_hole_cards(1, parse_cards("Kh Kc").unwrap()).unwrap();
+	let equities = heads_up.calc_equities(EquityMode::MonteCarlo(MonteCarloConfig::new(20000))).unwrap();
+	assert!((equities[0].equity - 0.82).abs() < 0.01);
+	assert!((equities[0].equity + equities[1].equity - 1.0).abs() < 1e-12);
+	assert!((equities[0].std_error - equities[1].std_error).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hand_vs_range_weights_every_combo() {
+	let mut game = HoldemGame::with_seed(2, 13);
+	game.set_front_cards(parse_cards("Ah Kh").unwrap()).unwrap();
+	game.set_board_cards(parse_cards("Kd 7c 2h").unwrap()).unwrap();
+	let range: Range = "QQ, 77:0.5".parse().unwrap();
+	game.set_range(1, range.clone()).unwrap();
+	let equity = game.calc_range_equity(EquityMode::Exact).unwrap();
+	assert!(equity.exact);
+	assert_eq!(equity.samples, (6 + 3) * 990);
+	assert_eq!(equity.combos.len(), 1);
+	assert_eq!(equity.combos[0].frequency, 1.0);
+	// the same as weighting the equity against each of villain's hands.
+	let (mut sum, mut weights) = (0.0, 0.0);
+	for (combo, weight) in range.without_cards(game.players[0].card_set()).combos() {
+		let mut heads_up = HoldemGame::with_seed(2, 13);
+		heads_up.set_front_cards(parse_cards("Ah Kh").unwrap()).unwrap();
+		heads_up.set_board_cards(parse_cards("Kd 7c 2h").unwrap()).unwrap();
+		heads_up.set_hole_cards(1, combo.cards().to_vec()).unwrap();
+		sum += weight * heads_up.calc_equity(EquityMode::Exact).unwrap().equity;
+		weights += weight;
+	}
+	assert!((equity.equity - sum / weights).abs() < 1e-12);
+	assert!((equity.combos[0].equity - equity.equity).abs() < 1e-12);
+    }
+
+    #[test]
+    fn range_vs_range_breakdown() {
+	let mut game = HoldemGame::with_seed(2, 13);
+	game.set_board_cards(parse_cards("Qs 7c 2h 3d 9s").unwrap()).unwrap();
+	game.set_range(0, "AA, KK".parse().unwrap()).unwrap();
+	game.set_range(1, "AA".parse().unwrap()).unwrap();
+	let equity = game.calc_range_equity(EquityMode::default()).unwrap();
+	assert!(equity.exact);
+	// each pair of aces leaves villain one combo, each pair of kings six.
+	assert_eq!(equity.samples, 6 + 36);
+	assert_eq!(equity.combos.len(), 12);
+	for combo in &equity.combos {
+		if combo.combo.high().rank == Rank::Ace {
+			assert!((combo.frequency - 1.0 / 42.0).abs() < 1e-12);
+			assert_eq!((combo.tie, combo.equity), (1.0, 0.5));
+		} else {
+			assert!((combo.frequency - 6.0 / 42.0).abs() < 1e-12);
+			assert_eq!(combo.equity, 0.0);
+		}
+	}
+	// the per-seat engine refuses ranges, the win rate goes through the range engine.
+	assert!(game.calc_equities(EquityMode::default()).is_err());
+	assert!(game.calc_equity(EquityMode::default()).is_err());
+	assert_eq!(game.calc_win_rate().unwrap(), 0.0);
+	assert!((equity.equity - 3.0 / 42.0).abs() < 1e-12);
+	assert!((equity.tie - 6.0 / 42.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn range_montecarlo_agrees_with_exact() {
+	let mut game = HoldemGame::with_seed(2, 13);
+	game.set_board_cards(parse_cards("Kd 7c 2h").unwrap()).unwrap();
+	game.set_range(0, "AA, KK".parse().unwrap()).unwrap();
+	game.set_range(1, "QQ+, AK:0.5".parse().unwrap()).unwrap();
+	let exact = game.calc_range_equity(EquityMode::Exact).unwrap();
+	let mode = EquityMode::MonteCarlo(MonteCarloConfig::new(20000));
+	let sampled = game.calc_range_equity_with_threads(mode, 1).unwrap();
+	assert!(!sampled.exact);
+	assert_eq!(sampled.samples, 20000);
+	assert!((sampled.equity - exact.equity).abs() < 4.0 * sampled.std_error);
+	assert_eq!(sampled, game.calc_range_equity_with_threads(mode, 3).unwrap());
+	let frequencies: f64 = sampled.combos.iter().map(|combo| combo.frequency).sum();
+	assert!((frequencies - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ranges_conflicting_with_known_cards() {
+	let mut game = HoldemGame::with_seed(2, 13);
+	game.set_front_cards(parse_cards("As Ad").unwrap()).unwrap();
+	assert!(game.set_range(0, "KK".parse().unwrap()).is_err());
+	assert!(game.set_range(2, "KK".parse().unwrap()).is_err());
+	game.set_range(1, "AsAd".parse().unwrap()).unwrap();
+	assert!(game.set_hole_cards(1, parse_cards("Kc Kd").unwrap()).is_err());
+	assert!(game.calc_range_equity(EquityMode::default()).is_err());
+	game.set_range(1, "AA, KK".parse().unwrap()).unwrap();
+	let equity = game.calc_range_equity(EquityMode::MonteCarlo(MonteCarloConfig::new(2000))).unwrap();
+	assert_eq!(equity.samples, 2000);
+    }
+
+    #[test]
+    fn too_few_cards_left() {
+	let mut game = HoldemGame::with_seed(10, 17);
+	game.set_dead_cards(Deck::new().deal_cards(33)).unwrap();
+	game.set_range(1, "AsKs".parse().unwrap()).unwrap();
+	let mode = EquityMode::Auto {exact_limit: DEFAULT_EXACT_LIMIT, montecarlo: MonteCarloConfig::default()};
+	assert!(game.calc_range_equity(mode).is_err());
+	let mut game = HoldemGame::with_seed(10, 17);
+	game.set_dead_cards(Deck::new().deal_cards(33)).unwrap();
+	assert!(game.calc_equities(mode).is_err());
+    }
+
+    #[test]
+    fn play_once_deals_a_full_board() {
+	let mut game = HoldemGame::with_seed(4, 5);
+	let ranks = game.play_once();
+	assert_eq!(ranks.len(), 4);
+	assert_eq!(game.community_cards.len(), 5);
+	assert!(game.players.iter().all(|player| player.len() == 7));
+    }
 }
@@ -0,0 +1,368 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::cardset::CardSet;
+use crate::deck::{Card, Deck};
+use crate::evaluator::HandCategory;
+use crate::grade::Grade;
+use crate::hand::Hand;
+use crate::holdem::{HoldemGame, Stage};
+use crate::range::Range;
+use rand_chacha::ChaCha12Rng;
+
+// bumped whenever one of the formats below changes.
+pub const JSON_VERSION: u32 = 1;
+
+// json formats, version 1. a card is always a rank-suit string such as "As" or "Td", and
+// every other value is an object carrying the version :
+//   deck   {"version": 1, "cards": [52 cards, the dealt ones first], "cursor": 3}
+//   hand   {"version": 1, "cards": ["As", "Kd"]}
+//   grade  {"version": 1, "category": "two_pairs", "cards": [the made hand, 5 cards]}
+//   game   {"version": 1, "stage": "flop", "board": [..], "hole_cards": [[..], ..],
+//           "dead_cards": [..], "ranges": [null or "AsKd, QhQc:0.5", ..], "deck": deck,
+//           "rng": the chacha12 state}
+// reading checks that the value is one this crate could have built.
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string(value)
+}
+
+pub fn to_json_pretty<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(value)
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+fn check_version<E: serde::de::Error>(version: u32) -> Result<(), E> {
+    match version {
+        JSON_VERSION => Ok(()),
+        _ => Err(E::custom(format!("unsupported json version {}", version))),
+    }
+}
+
+fn distinct<E: serde::de::Error>(cards: &[Card]) -> Result<CardSet, E> {
+    let set: CardSet = cards.iter().collect();
+    match set.len() == cards.len() {
+        true => Ok(set),
+        false => Err(E::custom("a card appears more than once")),
+    }
+}
+
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_text())
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DeckJson {
+    version: u32,
+    cards: Vec<Card>,
+    cursor: usize,
+}
+
+impl Serialize for Deck {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DeckJson { version: JSON_VERSION, cards: self.cards.to_vec(), cursor: self.cursor }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Deck {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = DeckJson::deserialize(deserializer)?;
+        check_version(json.version)?;
+        let cards: [Card; 52] = json.cards.try_into().map_err(|_| D::Error::custom("a deck holds 52 cards"))?;
+        distinct::<D::Error>(&cards)?;
+        if json.cursor > cards.len() {
+            return Err(D::Error::custom("the deck cursor is past the last card"));
+        }
+        Ok(Self { cards, cursor: json.cursor })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HandJson {
+    version: u32,
+    cards: Vec<Card>,
+}
+
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HandJson { version: JSON_VERSION, cards: self.cards.clone() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = HandJson::deserialize(deserializer)?;
+        check_version(json.version)?;
+        if json.cards.len() > 7 {
+            return Err(D::Error::custom("a hand holds at most 7 cards"));
+        }
+        distinct::<D::Error>(&json.cards)?;
+        let mut hand = Hand::new();
+        hand.add_cards(&json.cards);
+        Ok(hand)
+    }
+}
+
+fn category_name(category: HandCategory) -> &'static str {
+    match category {
+        HandCategory::StraightFlush => "straight_flush",
+        HandCategory::FourOfAKind => "four_of_a_kind",
+        HandCategory::FullHouse => "full_house",
+        HandCategory::Flush => "flush",
+        HandCategory::Straight => "straight",
+        HandCategory::ThreeOfAKind => "three_of_a_kind",
+        HandCategory::TwoPairs => "two_pairs",
+        HandCategory::OnePair => "one_pair",
+        HandCategory::Top => "top",
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GradeJson {
+    version: u32,
+    category: String,
+    cards: [Card; 5],
+}
+
+impl Serialize for Grade {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let category = category_name(self.rank().category()).to_string();
+        GradeJson { version: JSON_VERSION, category, cards: self.cards() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Grade {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = GradeJson::deserialize(deserializer)?;
+        check_version(json.version)?;
+        distinct::<D::Error>(&json.cards)?;
+        // the cards must be the made hand : in its rank order, and of the category given.
+        let made = Grade::new(&json.cards);
+        let category = made.rank().category();
+        if category_name(category) != json.category || made.cards().map(|card| card.rank) != json.cards.map(|card| card.rank) {
+            return Err(D::Error::custom("the grade does not match its cards"));
+        }
+        Ok(match category {
+            HandCategory::StraightFlush => Grade::StraightFlush(json.cards),
+            HandCategory::FourOfAKind => Grade::FourOfAKind(json.cards),
+            HandCategory::FullHouse => Grade::FullHouse(json.cards),
+            HandCategory::Flush => Grade::Flush(json.cards),
+            HandCategory::Straight => Grade::Straight(json.cards),
+            HandCategory::ThreeOfAKind => Grade::ThreeOfAKind(json.cards),
+            HandCategory::TwoPairs => Grade::TwoPairs(json.cards),
+            HandCategory::OnePair => Grade::OnePair(json.cards),
+            HandCategory::Top => Grade::Top(json.cards),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StageJson {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl From<Stage> for StageJson {
+    fn from(stage: Stage) -> Self {
+        match stage {
+            Stage::PreFlop => Self::PreFlop,
+            Stage::Flop => Self::Flop,
+            Stage::Turn => Self::Turn,
+            Stage::River => Self::River,
+        }
+    }
+}
+
+// a range is written in range notation, one token per combo.
+fn range_text(range: &Range) -> String {
+    range
+        .combos()
+        .map(|(combo, weight)| {
+            let [high, low] = combo.cards();
+            match weight < 1.0 {
+                true => format!("{}{}:{}", high.to_text(), low.to_text(), weight),
+                false => format!("{}{}", high.to_text(), low.to_text()),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameJson {
+    version: u32,
+    stage: StageJson,
+    board: Vec<Card>,
+    hole_cards: Vec<Vec<Card>>,
+    dead_cards: Vec<Card>,
+    ranges: Vec<Option<String>>,
+    deck: Deck,
+    rng: ChaCha12Rng,
+}
+
+impl Serialize for HoldemGame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let board: CardSet = self.community_cards.iter().collect();
+        GameJson {
+            version: JSON_VERSION,
+            stage: self.stage.into(),
+            board: self.community_cards.clone(),
+            hole_cards: self.players.iter().map(|hand| hand.cards.iter().copied().filter(|&card| !board.contains(card)).collect()).collect(),
+            dead_cards: self.dead_cards.to_vec(),
+            ranges: self.ranges.iter().map(|range| range.as_ref().map(range_text)).collect(),
+            deck: self.deck,
+            rng: self.rng.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HoldemGame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = GameJson::deserialize(deserializer)?;
+        check_version(json.version)?;
+        let players_cnt = json.hole_cards.len();
+        if players_cnt > 10 {
+            return Err(D::Error::custom("too many players on this game"));
+        }
+        if json.ranges.len() != players_cnt {
+            return Err(D::Error::custom("one range entry per player is needed"));
+        }
+        let stage = match json.board.len() {
+            0..=2 => Stage::PreFlop,
+            3 => Stage::Flop,
+            4 => Stage::Turn,
+            5 => Stage::River,
+            _ => return Err(D::Error::custom("community cards cannot be over 5 cards")),
+        };
+        if StageJson::from(stage) as u8 != json.stage as u8 {
+            return Err(D::Error::custom("the stage does not match the board"));
+        }
+        if json.hole_cards.iter().any(|cards| cards.len() > 2) {
+            return Err(D::Error::custom("hole cards cannot be over 2 cards"));
+        }
+        let known: Vec<Card> = json.board.iter().chain(json.hole_cards.iter().flatten()).chain(&json.dead_cards).copied().collect();
+        let known = distinct::<D::Error>(&known)?;
+        if !known.is_disjoint(json.deck.remaining()) {
+            return Err(D::Error::custom("a known card is still in the deck"));
+        }
+        let mut ranges = Vec::with_capacity(players_cnt);
+        for (seat, range) in json.ranges.iter().enumerate() {
+            let range = match range {
+                Some(text) if !json.hole_cards[seat].is_empty() => return Err(D::Error::custom(format!("seat {} holds both cards and a range: {}", seat, text))),
+                Some(text) => Some(text.parse::<Range>().map_err(D::Error::custom)?),
+                None => None,
+            };
+            ranges.push(range);
+        }
+        let players = json
+            .hole_cards
+            .iter()
+            .map(|cards| {
+                let mut hand = Hand::new();
+                hand.add_cards(cards);
+                hand.add_cards(&json.board);
+                hand
+            })
+            .collect();
+        Ok(Self {
+            deck: json.deck,
+            stage,
+            community_cards: json.board,
+            players_cnt,
+            players,
+            dead_cards: json.dead_cards.iter().collect(),
+            ranges,
+            rng: json.rng,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_for_json {
+    use crate::deck::*;
+    use crate::equity::*;
+    use crate::grade::Grade;
+    use crate::hand::Hand;
+    use crate::holdem::HoldemGame;
+    use crate::json::*;
+
+    #[test]
+    fn cards_decks_and_hands_round_trip() {
+        let card: Card = "Td".parse().unwrap();
+        assert_eq!(to_json(&card).unwrap(), "\"Td\"");
+        assert_eq!(from_json::<Card>("\"SA\"").unwrap(), "As".parse().unwrap());
+        assert!(from_json::<Card>("\"Zz\"").is_err());
+
+        let mut deck = Deck::new();
+        deck.deal_cards(3);
+        let json = to_json(&deck).unwrap();
+        assert!(json.starts_with("{\"version\":1,\"cards\":[\"2s\",\"3s\",\"4s\","));
+        assert!(json.ends_with("],\"cursor\":3}"));
+        let back: Deck = from_json(&json).unwrap();
+        assert_eq!((back.cards, back.cursor), (deck.cards, deck.cursor));
+        assert!(from_json::<Deck>(&json.replace("\"3s\"", "\"2s\"")).is_err());
+        assert!(from_json::<Deck>(&json.replace("\"cursor\":3", "\"cursor\":53")).is_err());
+
+        let hand: Hand = parse_cards("As Kd 7c").unwrap().iter().collect::<crate::cardset::CardSet>().into();
+        let json = to_json(&hand).unwrap();
+        let back: Hand = from_json(&json).unwrap();
+        assert_eq!(back.cards, hand.cards);
+        assert!(from_json::<Hand>(r#"{"version":2,"cards":["As"]}"#).is_err());
+        assert!(from_json::<Hand>(r#"{"version":1,"cards":["As","As"]}"#).is_err());
+    }
+
+    #[test]
+    fn grades_round_trip() {
+        let grade = Grade::from_set(parse_cards("Kh Kd 9c 9s 2h 3d 4c").unwrap().iter().collect());
+        let json = to_json(&grade).unwrap();
+        assert_eq!(json, r#"{"version":1,"category":"two_pairs","cards":["Kh","Kd","9c","9s","4c"]}"#);
+        let back: Grade = from_json(&json).unwrap();
+        assert_eq!(back.cards(), grade.cards());
+        // the cards decide the grade : a mislabelled or reordered one is refused.
+        assert!(from_json::<Grade>(&json.replace("two_pairs", "one_pair")).is_err());
+        assert!(from_json::<Grade>(r#"{"version":1,"category":"two_pairs","cards":["4c","Kh","Kd","9c","9s"]}"#).is_err());
+    }
+
+    #[test]
+    fn games_round_trip_and_carry_on() {
+        let mut game = HoldemGame::with_seed(3, 11);
+        game.set_hole_cards(0, parse_cards("Ah Ad").unwrap()).unwrap();
+        game.set_range(2, "QQ+, AKs:0.5".parse().unwrap()).unwrap();
+        game.set_dead_cards(parse_cards("2c").unwrap()).unwrap();
+        game.set_board_cards(parse_cards("Ks 7d 2h").unwrap()).unwrap();
+        let json = to_json(&game).unwrap();
+        assert!(json.starts_with(r#"{"version":1,"stage":"flop","board":["Ks","7d","2h"],"hole_cards":[["Ah","Ad"],[],[]],"dead_cards":["2c"],"ranges":[null,null,""#));
+        let back: HoldemGame = from_json(&json).unwrap();
+        assert_eq!(to_json(&back).unwrap(), json);
+        assert_eq!(back.board_cards(), game.board_cards());
+        assert_eq!(back.hole_cards(0), game.hole_cards(0));
+        // the rng is restored too, so both games go on the same way.
+        let mode = EquityMode::MonteCarlo(MonteCarloConfig::new(2000));
+        assert_eq!(back.calc_range_equity_with_threads(mode, 1).unwrap(), game.calc_range_equity_with_threads(mode, 1).unwrap());
+        let (mut game, mut back) = (game, back);
+        assert_eq!(back.play_once(), game.play_once());
+        assert_eq!(to_json_pretty(&back).unwrap(), to_json_pretty(&game).unwrap());
+
+        let json = to_json(&HoldemGame::with_seed(2, 3)).unwrap();
+        assert!(from_json::<HoldemGame>(&json).is_ok());
+        assert!(from_json::<HoldemGame>(&json.replace("\"version\":1", "\"version\":9")).is_err());
+        assert!(from_json::<HoldemGame>(&json.replace("\"stage\":\"pre_flop\"", "\"stage\":\"river\"")).is_err());
+        // known cards must have left the deck.
+        assert!(from_json::<HoldemGame>(&json.replace("\"board\":[]", "\"board\":[\"As\",\"Ks\",\"Qs\"]").replace("pre_flop", "flop")).is_err());
+    }
+}
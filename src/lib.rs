@@ -0,0 +1,21 @@
+extern crate rand;
+
+pub mod agent;
+pub mod arena;
+pub mod betting;
+pub mod cardset;
+pub mod deck;
+pub mod encoding;
+pub mod env;
+pub mod equity;
+pub mod evaluator;
+pub mod hand;
+pub mod history;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod observation;
+pub mod grade;
+pub mod holdem;
+pub mod pot;
+pub mod range;
+pub mod vec_env;
@@ -1,14 +1,5 @@
-mod deck;
-mod hand;
-mod grade;
-mod holdem;
-extern crate rand;
-
-use std::collections::HashSet;
-use crate::deck::{Card, Deck};
-use crate::hand::Hand;
-use crate::grade::*;
-use crate::holdem::*;
+use poker_env::deck::{Card, Deck};
+use poker_env::grade::*;
 
 fn main() {
         let mut deck = Deck::new();
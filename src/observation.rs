@@ -0,0 +1,162 @@
+use crate::betting::{ActionRecord, Blinds, HoldemTable, LegalActions};
+use crate::cardset::CardSet;
+use crate::deck::Card;
+use crate::env::DiscreteAction;
+use crate::holdem::Stage;
+
+// what one seat knows about a hand : its own hole cards and everything public. cards of other
+// seats only show up once they are turned over at showdown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub seat: usize,
+    // the seat to act, none once the hand is over.
+    pub to_act: Option<usize>,
+    pub button: usize,
+    pub blinds: Blinds,
+    pub stage: Stage,
+    pub hole_cards: CardSet,
+    pub board: Vec<Card>,
+    pub history: Vec<ActionRecord>,
+    pub pot: u64,
+    pub stacks: Vec<u64>,
+    pub street_bets: Vec<u64>,
+    pub contributions: Vec<u64>,
+    pub folded: Vec<bool>,
+    // hole cards turned over at showdown, for every seat.
+    pub shown: Vec<Option<CardSet>>,
+    // only set on the observing seat's turn.
+    pub legal_actions: Option<LegalActions>,
+    pub action_mask: [bool; DiscreteAction::COUNT],
+}
+
+impl Observation {
+    pub fn new(table: &HoldemTable, seat: usize) -> Self {
+        let seats = table.seats();
+        let folded: Vec<bool> = (0..seats).map(|other| table.is_folded(other)).collect();
+        let showdown = table.is_finished() && folded.iter().filter(|&&folded| !folded).count() >= 2;
+        let shown = (0..seats)
+            .map(|other| (showdown && !folded[other]).then(|| table.game().hole_cards(other)))
+            .collect();
+        let legal_actions = table.legal_actions().filter(|_| table.to_act() == Some(seat));
+        let mut action_mask = [false; DiscreteAction::COUNT];
+        if let Some(legal) = &legal_actions {
+            for action in DiscreteAction::ALL {
+                action_mask[action.index()] = action.to_action(legal, table.pot()).is_some();
+            }
+        }
+        Self {
+            seat,
+            to_act: table.to_act(),
+            button: table.button(),
+            blinds: table.blinds(),
+            stage: table.stage(),
+            hole_cards: table.game().hole_cards(seat),
+            board: table.game().board_cards().to_vec(),
+            history: table.history().to_vec(),
+            pot: table.pot(),
+            stacks: table.stacks().to_vec(),
+            street_bets: table.street_bets().to_vec(),
+            contributions: table.contributions().to_vec(),
+            folded,
+            shown,
+            legal_actions,
+            action_mask,
+        }
+    }
+
+    pub fn is_my_turn(&self) -> bool {
+        self.to_act == Some(self.seat)
+    }
+}
+
+// everything about a hand, hidden cards included. meant for logging and replays, never for
+// the agents playing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OmniscientView {
+    pub to_act: Option<usize>,
+    pub button: usize,
+    pub stage: Stage,
+    pub hole_cards: Vec<CardSet>,
+    pub board: Vec<Card>,
+    pub dead_cards: CardSet,
+    pub history: Vec<ActionRecord>,
+    pub starting_stacks: Vec<u64>,
+    pub stacks: Vec<u64>,
+    pub contributions: Vec<u64>,
+    pub folded: Vec<bool>,
+    pub winnings: Vec<u64>,
+}
+
+impl OmniscientView {
+    pub fn new(table: &HoldemTable) -> Self {
+        let seats = table.seats();
+        Self {
+            to_act: table.to_act(),
+            button: table.button(),
+            stage: table.stage(),
+            hole_cards: (0..seats).map(|seat| table.game().hole_cards(seat)).collect(),
+            board: table.game().board_cards().to_vec(),
+            dead_cards: table.game().dead_cards(),
+            history: table.history().to_vec(),
+            starting_stacks: table.starting_stacks().to_vec(),
+            stacks: table.stacks().to_vec(),
+            contributions: table.contributions().to_vec(),
+            folded: (0..seats).map(|seat| table.is_folded(seat)).collect(),
+            winnings: table.winnings().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_for_observation {
+    use crate::betting::*;
+    use crate::cardset::CardSet;
+    use crate::holdem::HoldemGame;
+    use crate::observation::*;
+
+    fn table(players: usize, seed: u64) -> HoldemTable {
+        HoldemTable::new(HoldemGame::with_seed(players, seed), vec![100; players], 0, Blinds::new(1, 2)).unwrap()
+    }
+
+    #[test]
+    fn observation_hides_other_hands() {
+        let mut table = table(3, 5);
+        table.act(Action::Call).unwrap();
+        let view = OmniscientView::new(&table);
+        for seat in 0..3 {
+            let obs = Observation::new(&table, seat);
+            assert_eq!(obs.hole_cards, view.hole_cards[seat]);
+            assert_eq!(obs.shown, vec![None; 3]);
+            assert_eq!(obs.history, view.history);
+            // nothing in the observation names another seat's cards.
+            let text = format!("{:?}", obs);
+            for other in (0..3).filter(|&other| other != seat) {
+                for card in view.hole_cards[other].iter() {
+                    assert!(!text.contains(&format!("{:?}", card)));
+                }
+            }
+            assert_eq!(obs.is_my_turn(), seat == 1);
+            assert_eq!(obs.legal_actions.is_some(), seat == 1);
+            assert_eq!(obs.action_mask.iter().any(|&legal| legal), seat == 1);
+        }
+    }
+
+    #[test]
+    fn showdown_turns_cards_over() {
+        let mut all_in = table(3, 9);
+        all_in.act(Action::Fold).unwrap();
+        all_in.act(Action::Raise(100)).unwrap();
+        all_in.act(Action::Call).unwrap();
+        assert!(all_in.is_finished());
+        let view = OmniscientView::new(&all_in);
+        let obs = Observation::new(&all_in, 0);
+        assert_eq!(obs.shown, vec![None, Some(view.hole_cards[1]), Some(view.hole_cards[2])]);
+        assert_eq!(obs.board.len(), 5);
+        assert_eq!(view.winnings.iter().sum::<u64>(), 200);
+        // a hand won without a showdown shows nothing.
+        let mut heads_up = table(2, 1);
+        heads_up.act(Action::Fold).unwrap();
+        assert_eq!(Observation::new(&heads_up, 1).shown, vec![None, None]);
+        assert_ne!(OmniscientView::new(&heads_up).hole_cards[0], CardSet::new());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::grade::Grade;
+
+// a pot and the seats that can win it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u64,
+    pub eligible: Vec<usize>,
+}
+
+// who gets the chips left over when a pot does not split evenly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OddChipRule {
+    // one chip each to the winners, starting from the first one left of the button.
+    #[default]
+    LeftOfButton,
+    // one chip each to the winners, starting from the lowest seat.
+    LowestSeat,
+}
+
+// splits the chips each seat put in into the main pot and the side pots, the main pot first.
+// every all-in amount of a player still in the hand closes a pot. chips of folded seats stay
+// in the pots they reach, and a last pot with a single eligible seat is an uncalled bet.
+pub fn build_pots(contributions: &[u64], folded: &[bool]) -> Vec<Pot> {
+    let mut levels: Vec<u64> = (0..contributions.len())
+        .filter(|&seat| !folded[seat])
+        .map(|seat| contributions[seat])
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut floor = 0;
+    for &level in levels.iter().filter(|&&level| level > 0) {
+        let amount = contributions.iter().map(|&chips| chips.min(level) - chips.min(floor)).sum();
+        let eligible = (0..contributions.len())
+            .filter(|&seat| !folded[seat] && contributions[seat] >= level)
+            .collect();
+        pots.push(Pot { amount, eligible });
+        floor = level;
+    }
+    let beyond: u64 = contributions.iter().map(|&chips| chips.saturating_sub(floor)).sum();
+    if let Some(pot) = pots.last_mut() {
+        pot.amount += beyond;
+    }
+    pots
+}
+
+// hands each pot to its eligible seats holding the best grade, splitting ties. a pot with a
+// single eligible seat needs no grade, any other needs at least one. returns the chips won
+// per seat.
+pub fn award_pots(pots: &[Pot], grades: &[Option<Grade>], button: usize, rule: OddChipRule) -> Result<Vec<u64>, &'static str> {
+    let seats = grades.len();
+    let mut winnings = vec![0; seats];
+    for pot in pots {
+        let mut winners: Vec<usize> = match pot.eligible[..] {
+            [] => continue,
+            [winner] => vec![winner],
+            _ => {
+                let best = pot
+                    .eligible
+                    .iter()
+                    .filter_map(|&seat| grades[seat].as_ref())
+                    .reduce(|best, grade| if grade > best { grade } else { best });
+                if best.is_none() {
+                    return Err("A pot goes to showdown without a known hand.");
+                }
+                pot.eligible.iter().copied().filter(|&seat| grades[seat].as_ref() == best).collect()
+            }
+        };
+        match rule {
+            OddChipRule::LeftOfButton => winners.sort_by_key(|&seat| (seat + seats - button - 1) % seats),
+            OddChipRule::LowestSeat => winners.sort_unstable(),
+        }
+        let share = pot.amount / winners.len() as u64;
+        let odd_chips = (pot.amount % winners.len() as u64) as usize;
+        for (i, &winner) in winners.iter().enumerate() {
+            winnings[winner] += share + u64::from(i < odd_chips);
+        }
+    }
+    Ok(winnings)
+}
+
+#[cfg(test)]
+mod test_for_pot {
+    use crate::cardset::CardSet;
+    use crate::deck::*;
+    use crate::grade::Grade;
+    use crate::pot::*;
+
+    // hole cards on the board 2c 7d 9h Js Kd.
+    fn grade(hole: &str) -> Option<Grade> {
+        let cards: CardSet = parse_cards(&format!("2c 7d 9h Js Kd {}", hole)).unwrap().iter().collect();
+        Some(Grade::from_set(cards))
+    }
+
+    #[test]
+    fn equal_contributions_make_one_pot() {
+        let pots = build_pots(&[100, 100, 100], &[false, false, false]);
+        assert_eq!(pots, vec![Pot { amount: 300, eligible: vec![0, 1, 2] }]);
+        let pots = build_pots(&[100, 40, 100], &[false, true, false]);
+        assert_eq!(pots, vec![Pot { amount: 240, eligible: vec![0, 2] }]);
+    }
+
+    #[test]
+    fn three_way_all_in() {
+        // seat 0 is all-in for 50, seat 1 for 100 and seat 2 covers both.
+        let pots = build_pots(&[50, 100, 200], &[false, false, false]);
+        assert_eq!(
+            pots,
+            vec![
+                Pot { amount: 150, eligible: vec![0, 1, 2] },
+                Pot { amount: 100, eligible: vec![1, 2] },
+                Pot { amount: 100, eligible: vec![2] },
+            ]
+        );
+        // the short stack has the best hand, the middle one the second best.
+        let grades = [grade("Kh Ks"), grade("As Ah"), grade("3s 4h")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![150, 100, 100]);
+        // the big stack wins everything when it has the best hand.
+        let grades = [grade("Qs Qh"), grade("As Ah"), grade("Th 8s")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![0, 0, 350]);
+    }
+
+    #[test]
+    fn three_way_all_in_with_a_split_side_pot() {
+        let pots = build_pots(&[30, 90, 90], &[false, false, false]);
+        assert_eq!(pots.len(), 2);
+        // seats 1 and 2 chop the side pot, the short stack wins the main pot.
+        let grades = [grade("Th 8s"), grade("3s 4h"), grade("3d 4s")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![90, 60, 60]);
+        let grades = [grade("3c 4c"), grade("3s 4h"), grade("3d 4s")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![30, 90, 90]);
+    }
+
+    #[test]
+    fn four_way_all_in_with_a_folded_seat() {
+        // seat 3 put in 60 and folded : its chips stay in the pots they reach.
+        let contributions = [25, 80, 150, 60];
+        let folded = [false, false, false, true];
+        let pots = build_pots(&contributions, &folded);
+        assert_eq!(
+            pots,
+            vec![
+                Pot { amount: 100, eligible: vec![0, 1, 2] },
+                Pot { amount: 35 + 55 + 55, eligible: vec![1, 2] },
+                Pot { amount: 70, eligible: vec![2] },
+            ]
+        );
+        assert_eq!(pots.iter().map(|pot| pot.amount).sum::<u64>(), contributions.iter().sum::<u64>());
+        let grades = [grade("Th 8s"), grade("As Ah"), grade("Qs Qh"), None];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![100, 145, 70, 0]);
+    }
+
+    #[test]
+    fn four_way_all_in_with_ties_everywhere() {
+        let contributions = [40, 40, 100, 100];
+        let pots = build_pots(&contributions, &[false; 4]);
+        assert_eq!(pots.len(), 2);
+        // seats 0 and 2 tie for the main pot and seat 2 wins the side pot.
+        let grades = [grade("Th 8s"), grade("3s 4h"), grade("Tc 8c"), grade("Qs Qh")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![80, 0, 200, 0]);
+        // everyone ties.
+        let grades = [grade("3c 4c"), grade("3s 4h"), grade("3d 4s"), grade("3h 4d")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), contributions.to_vec());
+    }
+
+    #[test]
+    fn odd_chips_follow_the_rule() {
+        let pots = build_pots(&[50, 51, 50], &[false, true, false]);
+        assert_eq!(pots[0].amount, 151);
+        let grades = [grade("3c 4c"), None, grade("3d 4s")];
+        // the button is seat 1 : seat 2 sits first on its left.
+        assert_eq!(award_pots(&pots, &grades, 1, OddChipRule::LeftOfButton).unwrap(), vec![75, 0, 76]);
+        assert_eq!(award_pots(&pots, &grades, 1, OddChipRule::LowestSeat).unwrap(), vec![76, 0, 75]);
+        assert_eq!(award_pots(&pots, &grades, 2, OddChipRule::LeftOfButton).unwrap(), vec![76, 0, 75]);
+    }
+
+    #[test]
+    fn uncalled_bet_comes_back() {
+        let pots = build_pots(&[300, 100], &[false, false]);
+        assert_eq!(pots[1], Pot { amount: 200, eligible: vec![0] });
+        let grades = [grade("3c 4c"), grade("As Ah")];
+        assert_eq!(award_pots(&pots, &grades, 0, OddChipRule::default()).unwrap(), vec![200, 200]);
+    }
+
+    #[test]
+    fn showdown_without_a_known_hand() {
+        let pots = [Pot { amount: 10, eligible: vec![0, 1] }];
+        assert!(award_pots(&pots, &[None, None], 0, OddChipRule::LowestSeat).is_err());
+        assert_eq!(award_pots(&pots, &[None, grade("3c 4c")], 0, OddChipRule::LowestSeat), Ok(vec![0, 10]));
+    }
+}
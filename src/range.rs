@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use crate::cardset::CardSet;
+use crate::deck::{Card, Rank, Suit};
+
+// two hole cards, the higher one first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct Combo([Card; 2]);
+
+// a set of combos, each held with a weight in (0, 1].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Range {
+    weights: BTreeMap<Combo, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRangeError {
+    Empty,
+    InvalidToken(String),
+    InvalidWeight(String),
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no hand in range"),
+            Self::InvalidToken(token) => write!(f, "invalid range token: {:?}", token),
+            Self::InvalidWeight(weight) => write!(f, "weight must be in (0, 1]: {:?}", weight),
+        }
+    }
+}
+
+impl std::error::Error for ParseRangeError {}
+
+impl Combo {
+    pub fn new(a: Card, b: Card) -> Option<Self> {
+        match a.cmp(&b) {
+            std::cmp::Ordering::Greater => Some(Self([a, b])),
+            std::cmp::Ordering::Less => Some(Self([b, a])),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn cards(self) -> [Card; 2] {
+        self.0
+    }
+
+    pub fn high(self) -> Card {
+        self.0[0]
+    }
+
+    pub fn low(self) -> Card {
+        self.0[1]
+    }
+
+    pub fn card_set(self) -> CardSet {
+        self.0.iter().collect()
+    }
+
+    pub fn is_pair(self) -> bool {
+        self.0[0].rank == self.0[1].rank
+    }
+
+    pub fn is_suited(self) -> bool {
+        self.0[0].suit == self.0[1].suit
+    }
+}
+
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.0[0], self.0[1])
+    }
+}
+
+impl Range {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // every one of the 1326 combos.
+    pub fn full() -> Self {
+        let cards = CardSet::FULL.to_vec();
+        let combos = cards.iter().enumerate().flat_map(|(i, &a)| cards[i + 1..].iter().filter_map(move |&b| Combo::new(a, b)));
+        combos.collect()
+    }
+
+    // a weight of 0 removes the combo. weights above 1 are cut to 1.
+    pub fn insert(&mut self, combo: Combo, weight: f64) {
+        if weight > 0.0 {
+            self.weights.insert(combo, weight.min(1.0));
+        } else {
+            self.weights.remove(&combo);
+        }
+    }
+
+    pub fn remove(&mut self, combo: Combo) -> bool {
+        self.weights.remove(&combo).is_some()
+    }
+
+    pub fn contains(&self, combo: Combo) -> bool {
+        self.weights.contains_key(&combo)
+    }
+
+    pub fn weight(&self, combo: Combo) -> f64 {
+        self.weights.get(&combo).copied().unwrap_or(0.0)
+    }
+
+    // number of combos, whatever their weights.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    // number of combos counted by weight.
+    pub fn total_weight(&self) -> f64 {
+        self.weights.values().sum()
+    }
+
+    pub fn combos(&self) -> impl Iterator<Item = (Combo, f64)> + '_ {
+        self.weights.iter().map(|(&combo, &weight)| (combo, weight))
+    }
+
+    // drops the combos holding any of the known cards.
+    pub fn without_cards(&self, known: CardSet) -> Self {
+        let weights = self
+            .combos()
+            .filter(|(combo, _)| combo.card_set().is_disjoint(known))
+            .collect();
+        Self { weights }
+    }
+
+    // combos of either range, with the larger weight.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut range = self.clone();
+        for (combo, weight) in other.combos() {
+            range.insert(combo, weight.max(self.weight(combo)));
+        }
+        range
+    }
+
+    // combos of both ranges, with the smaller weight.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let weights = self
+            .combos()
+            .filter(|&(combo, _)| other.contains(combo))
+            .map(|(combo, weight)| (combo, weight.min(other.weight(combo))))
+            .collect();
+        Self { weights }
+    }
+
+    // combos of this range that are not in the other.
+    pub fn difference(&self, other: &Self) -> Self {
+        let weights = self.combos().filter(|&(combo, _)| !other.contains(combo)).collect();
+        Self { weights }
+    }
+}
+
+impl FromIterator<Combo> for Range {
+    fn from_iter<I: IntoIterator<Item = Combo>>(iter: I) -> Self {
+        let weights = iter.into_iter().map(|combo| (combo, 1.0)).collect();
+        Self { weights }
+    }
+}
+
+// a hand class such as "AKs", "AKo", "AK" or "TT".
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Class {
+    high: Rank,
+    low: Rank,
+    suited: Option<bool>,
+}
+
+impl Class {
+    fn parse(token: &str) -> Option<Self> {
+        let chars: Vec<char> = token.chars().collect();
+        let (a, b, suited) = match chars[..] {
+            [a, b] => (a, b, None),
+            [a, b, 's' | 'S'] => (a, b, Some(true)),
+            [a, b, 'o' | 'O'] => (a, b, Some(false)),
+            _ => return None,
+        };
+        let (a, b) = (Rank::from_char(a)?, Rank::from_char(b)?);
+        let (high, low) = (a.max(b), a.min(b));
+        if high == low && suited.is_some() {
+            return None;
+        }
+        Some(Self { high, low, suited })
+    }
+
+    fn with_ranks(self, high: Rank, low: Rank) -> Self {
+        Self { high, low, ..self }
+    }
+
+    fn is_pair(self) -> bool {
+        self.high == self.low
+    }
+
+    fn combos(self) -> Vec<Combo> {
+        let mut combos = Vec::new();
+        for &a in &Suit::ALL {
+            for &b in &Suit::ALL {
+                let suited = a == b;
+                let wanted = match self.suited {
+                    _ if self.is_pair() => a < b,
+                    Some(only_suited) => suited == only_suited,
+                    None => true,
+                };
+                if wanted {
+                    combos.extend(Combo::new(Card::new(self.high, a), Card::new(self.low, b)));
+                }
+            }
+        }
+        combos
+    }
+
+    // the classes from self to last, both included : pairs step both ranks, other
+    // hands keep their high rank and step the low one.
+    fn span(self, last: Self) -> Option<Vec<Self>> {
+        if self.suited != last.suited || self.is_pair() != last.is_pair() {
+            return None;
+        }
+        if !self.is_pair() && self.high != last.high {
+            return None;
+        }
+        let (from, to) = (self.low.index().min(last.low.index()), self.low.index().max(last.low.index()));
+        let classes = (from..=to).map(|i| {
+            let rank = Rank::ALL[i];
+            if self.is_pair() { self.with_ranks(rank, rank) } else { self.with_ranks(self.high, rank) }
+        });
+        Some(classes.collect())
+    }
+
+    // "TT+" goes up to aces, "ATs+" up to the kicker right below the high card.
+    fn and_better(self) -> Vec<Self> {
+        let last = if self.is_pair() {
+            self.with_ranks(Rank::Ace, Rank::Ace)
+        } else {
+            self.with_ranks(self.high, Rank::ALL[self.high.index() - 1])
+        };
+        self.span(last).unwrap_or_default()
+    }
+}
+
+fn parse_combos(token: &str) -> Option<Vec<Combo>> {
+    if let Some(first) = token.strip_suffix('+') {
+        let classes = Class::parse(first)?.and_better();
+        return Some(classes.into_iter().flat_map(Class::combos).collect());
+    }
+    if let Some((first, last)) = token.split_once('-') {
+        let classes = Class::parse(first)?.span(Class::parse(last)?)?;
+        return Some(classes.into_iter().flat_map(Class::combos).collect());
+    }
+    if let Some(class) = Class::parse(token) {
+        return Some(class.combos());
+    }
+    // a single combo such as "AsKh".
+    if token.len() == 4 && token.is_ascii() {
+        let (a, b) = (token[..2].parse::<Card>().ok()?, token[2..].parse::<Card>().ok()?);
+        return Some(vec![Combo::new(a, b)?]);
+    }
+    None
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+
+    // parses notation such as "TT+, AKs, KQo, A5s-A2s, 76s:0.5, AsKh".
+    // a token may end with ":weight", which applies to each of its combos.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Self::new();
+        for token in s.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()) {
+            let (hands, weight) = match token.split_once(':') {
+                Some((hands, weight)) => match weight.parse::<f64>() {
+                    Ok(w) if w > 0.0 && w <= 1.0 => (hands, w),
+                    _ => return Err(ParseRangeError::InvalidWeight(weight.to_string())),
+                },
+                None => (token, 1.0),
+            };
+            let combos = parse_combos(hands).ok_or_else(|| ParseRangeError::InvalidToken(token.to_string()))?;
+            combos.into_iter().for_each(|combo| range.insert(combo, weight));
+        }
+        if range.is_empty() {
+            return Err(ParseRangeError::Empty);
+        }
+        Ok(range)
+    }
+}
+
+impl TryFrom<&str> for Range {
+    type Error = ParseRangeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod test_for_range {
+    use crate::cardset::*;
+    use crate::deck::*;
+    use crate::range::*;
+
+    fn range(s: &str) -> Range {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn hand_classes_expand_to_combos() {
+        assert_eq!(range("AA").len(), 6);
+        assert_eq!(range("AKs").len(), 4);
+        assert_eq!(range("AKo").len(), 12);
+        assert_eq!(range("AK").len(), 16);
+        assert_eq!(range("KA").len(), 16);
+        assert_eq!(range("TT+").len(), 30);
+        assert_eq!(range("ATs+").len(), 16);
+        assert_eq!(range("A5s-A2s").len(), 16);
+        assert_eq!(range("A2s-A5s"), range("A5s-A2s"));
+        assert_eq!(range("99-77").len(), 18);
+        assert_eq!(range("AsKh").len(), 1);
+        assert_eq!(Range::full().len(), 1326);
+        assert!(range("AKs").combos().all(|(combo, _)| combo.is_suited() && !combo.is_pair()));
+        assert!(range("AKo").combos().all(|(combo, _)| !combo.is_suited()));
+    }
+
+    #[test]
+    fn parse_full_notation() {
+        let range = range("TT+, AKs, KQo, A5s-A2s, 76s:0.5");
+        assert_eq!(range.len(), 30 + 4 + 12 + 16 + 4);
+        assert_eq!(range.total_weight(), 30.0 + 4.0 + 12.0 + 16.0 + 2.0);
+        let seven_six = Combo::new(Card::new(Rank::Seven, Suit::Heart), Card::new(Rank::Six, Suit::Heart)).unwrap();
+        assert_eq!(range.weight(seven_six), 0.5);
+        let aces = Combo::new(Card::new(Rank::Ace, Suit::Heart), Card::new(Rank::Ace, Suit::Club)).unwrap();
+        assert_eq!(range.weight(aces), 1.0);
+        assert_eq!(aces.high(), Card::new(Rank::Ace, Suit::Club));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Range>(), Err(ParseRangeError::Empty));
+        assert_eq!("AAs".parse::<Range>(), Err(ParseRangeError::InvalidToken("AAs".to_string())));
+        assert_eq!("AXs".parse::<Range>(), Err(ParseRangeError::InvalidToken("AXs".to_string())));
+        assert_eq!("A5s-K2s".parse::<Range>(), Err(ParseRangeError::InvalidToken("A5s-K2s".to_string())));
+        assert_eq!("A5s-A2o".parse::<Range>(), Err(ParseRangeError::InvalidToken("A5s-A2o".to_string())));
+        assert_eq!("AsAs".parse::<Range>(), Err(ParseRangeError::InvalidToken("AsAs".to_string())));
+        assert_eq!("AKs:1.5".parse::<Range>(), Err(ParseRangeError::InvalidWeight("1.5".to_string())));
+        assert_eq!("AKs:x".parse::<Range>(), Err(ParseRangeError::InvalidWeight("x".to_string())));
+    }
+
+    #[test]
+    fn known_cards_remove_combos() {
+        let known: CardSet = parse_cards("As Kd").unwrap().iter().collect();
+        assert_eq!(range("AA").without_cards(known).len(), 3);
+        assert_eq!(range("AK").without_cards(known).len(), 9);
+        assert_eq!(range("QQ").without_cards(known).len(), 6);
+    }
+
+    #[test]
+    fn set_operations() {
+        let wide = range("TT+, AK:0.5");
+        let narrow = range("QQ+:0.25, AK");
+        let union = wide.union(&narrow);
+        assert_eq!(union.len(), 30 + 16);
+        assert_eq!(union.total_weight(), 30.0 + 16.0);
+        let intersection = wide.intersection(&narrow);
+        assert_eq!(intersection.len(), 18 + 16);
+        assert_eq!(intersection.total_weight(), 18.0 * 0.25 + 16.0 * 0.5);
+        let difference = wide.difference(&narrow);
+        assert_eq!(difference, range("TT-JJ"));
+        let mut aces = range("AA");
+        let combo = aces.combos().next().unwrap().0;
+        aces.insert(combo, 0.0);
+        assert_eq!(aces.len(), 5);
+        assert!(!aces.contains(combo));
+    }
+}
@@ -0,0 +1,222 @@
+use std::thread;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::betting::Action;
+use crate::env::{DiscreteAction, EnvConfig, HoldemEnv, StepInfo};
+use crate::equity::stream_rng;
+use crate::observation::Observation;
+
+// what a batch step gives back, one entry per environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VecStep {
+    // a finished environment is already reset : its entry is the first observation of the next hand.
+    pub observations: Vec<Observation>,
+    pub rewards: Vec<Vec<f64>>,
+    pub dones: Vec<bool>,
+    pub infos: Vec<StepInfo>,
+    // the last observation of a hand that just ended.
+    pub final_observations: Vec<Option<Observation>>,
+}
+
+// many hold'em environments stepped together. every environment draws the seeds of its hands
+// from its own stream, derived from the master seed, so results do not depend on the threads.
+#[derive(Debug, Clone)]
+pub struct VecEnv {
+    envs: Vec<HoldemEnv>,
+    rngs: Vec<StdRng>,
+    threads: usize,
+}
+
+impl VecEnv {
+    pub fn new(config: EnvConfig, num_envs: usize, master_seed: u64) -> Result<Self, &'static str> {
+        if num_envs == 0 {return Err("A VecEnv needs at least one environment.");}
+        // every hand then has a decision to make and finished environments can reset.
+        if config.starting_stack <= config.blinds.ante + config.blinds.big_blind {
+            return Err("Stacks must cover more than the ante and the big blind.");
+        }
+        let env = HoldemEnv::new(config)?;
+        let rngs = (0..num_envs).map(|i| stream_rng(master_seed, i)).collect();
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        Ok(Self { envs: vec![env; num_envs], rngs, threads })
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    pub fn envs(&self) -> &[HoldemEnv] {
+        &self.envs
+    }
+
+    pub fn reset(&mut self) -> Vec<Observation> {
+        self.run(&vec![(); self.len()], |env, rng, _| env.reset(rng.gen()))
+    }
+
+    pub fn to_act(&self) -> Vec<Option<usize>> {
+        self.envs.iter().map(|env| env.to_act()).collect()
+    }
+
+    pub fn action_masks(&self) -> Vec<[bool; DiscreteAction::COUNT]> {
+        self.envs.iter().map(|env| env.action_mask()).collect()
+    }
+
+    // one action per environment, for its seat to act. nothing moves if any action is illegal.
+    pub fn step(&mut self, actions: &[Action]) -> Result<VecStep, &'static str> {
+        if actions.len() != self.len() {return Err("One action per environment is needed.");}
+        for (env, &action) in self.envs.iter().zip(actions) {
+            let legal = env.legal_actions().ok_or("Reset the environments first.")?;
+            legal.check(action)?;
+        }
+        let results = self.run(actions, |env, rng, &action| {
+            let (obs, rewards, done, info) = env.step(action).expect("the action was checked");
+            match done {
+                true => (env.reset(rng.gen()), rewards, done, info, Some(obs)),
+                false => (obs, rewards, done, info, None),
+            }
+        });
+        let mut step = VecStep {
+            observations: Vec::with_capacity(results.len()),
+            rewards: Vec::with_capacity(results.len()),
+            dones: Vec::with_capacity(results.len()),
+            infos: Vec::with_capacity(results.len()),
+            final_observations: Vec::with_capacity(results.len()),
+        };
+        for (obs, rewards, done, info, final_obs) in results {
+            step.observations.push(obs);
+            step.rewards.push(rewards);
+            step.dones.push(done);
+            step.infos.push(info);
+            step.final_observations.push(final_obs);
+        }
+        Ok(step)
+    }
+
+    pub fn step_discrete(&mut self, actions: &[DiscreteAction]) -> Result<VecStep, &'static str> {
+        if actions.len() != self.len() {return Err("One action per environment is needed.");}
+        let actions = self
+            .envs
+            .iter()
+            .zip(actions)
+            .map(|(env, &action)| env.to_action(action).ok_or("The action is masked out."))
+            .collect::<Result<Vec<Action>, &'static str>>()?;
+        self.step(&actions)
+    }
+
+    // runs `work` on every environment with its input, spread over the worker threads in
+    // contiguous chunks, and keeps the order.
+    fn run<I: Sync, T: Send>(&mut self, inputs: &[I], work: impl Fn(&mut HoldemEnv, &mut StdRng, &I) -> T + Sync) -> Vec<T> {
+        let chunk = self.len().div_ceil(self.threads.min(self.len()));
+        if chunk == self.len() {
+            return self.envs.iter_mut().zip(&mut self.rngs).zip(inputs).map(|((env, rng), input)| work(env, rng, input)).collect();
+        }
+        let work = &work;
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .envs
+                .chunks_mut(chunk)
+                .zip(self.rngs.chunks_mut(chunk))
+                .zip(inputs.chunks(chunk))
+                .map(|((envs, rngs), inputs)| {
+                    scope.spawn(move || {
+                        envs.iter_mut().zip(rngs).zip(inputs).map(|((env, rng), input)| work(env, rng, input)).collect::<Vec<T>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_for_vec_env {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use crate::betting::*;
+    use crate::env::*;
+    use crate::vec_env::*;
+
+    fn vec_env(threads: usize) -> VecEnv {
+        VecEnv::new(EnvConfig::new(3, 100, Blinds::new(1, 2)), 8, 42).unwrap().with_threads(threads)
+    }
+
+    // plays random legal actions for some steps and collects everything returned.
+    fn play(vec_env: &mut VecEnv, steps: usize) -> Vec<VecStep> {
+        let mut rng = StdRng::seed_from_u64(5);
+        vec_env.reset();
+        (0..steps)
+            .map(|_| {
+                let actions: Vec<DiscreteAction> = vec_env
+                    .action_masks()
+                    .iter()
+                    .map(|mask| {
+                        let legal: Vec<DiscreteAction> = DiscreteAction::ALL.into_iter().filter(|action| mask[action.index()]).collect();
+                        *legal.choose(&mut rng).unwrap()
+                    })
+                    .collect();
+                vec_env.step_discrete(&actions).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invalid_batches_are_rejected() {
+        assert!(VecEnv::new(EnvConfig::new(3, 100, Blinds::new(1, 2)), 0, 42).is_err());
+        assert!(VecEnv::new(EnvConfig::new(3, 2, Blinds::new(1, 2)), 4, 42).is_err());
+        let mut vec_env = vec_env(2);
+        assert!(vec_env.step(&[Action::Call; 8]).is_err());
+        let first = vec_env.reset();
+        assert_eq!(first.len(), 8);
+        // different seeds deal different hands.
+        assert!(first.iter().any(|obs| obs.hole_cards != first[0].hole_cards));
+        assert!(vec_env.step(&[Action::Call; 7]).is_err());
+        let mut actions = [Action::Call; 8];
+        actions[3] = Action::Check;
+        assert!(vec_env.step(&actions).is_err());
+        // nothing moved.
+        assert_eq!(vec_env.to_act(), vec![Some(0); 8]);
+    }
+
+    #[test]
+    fn finished_environments_reset() {
+        let mut vec_env = vec_env(3);
+        let steps = play(&mut vec_env, 60);
+        let mut hands = 0;
+        for step in &steps {
+            for i in 0..vec_env.len() {
+                match step.dones[i] {
+                    true => {
+                        hands += 1;
+                        assert_eq!(step.rewards[i].iter().sum::<f64>(), 0.0);
+                        assert!(step.final_observations[i].as_ref().unwrap().to_act.is_none());
+                    }
+                    false => {
+                        assert_eq!(step.rewards[i], vec![0.0; 3]);
+                        assert!(step.final_observations[i].is_none());
+                    }
+                }
+                // every environment always has a seat to act.
+                assert!(step.observations[i].to_act.is_some());
+            }
+        }
+        assert!(hands > 8);
+    }
+
+    #[test]
+    fn thread_count_does_not_change_results() {
+        let one = play(&mut vec_env(1), 30);
+        let three = play(&mut vec_env(3), 30);
+        let eight = play(&mut vec_env(8), 30);
+        assert_eq!(one, three);
+        assert_eq!(one, eight);
+    }
+}